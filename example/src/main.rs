@@ -1,16 +1,8 @@
 #[macro_use]
 extern crate xdr_rs_serialize_derive;
 
-#[allow(unused_imports)]
-use xdr_rs_serialize::de::{
-    read_fixed_array, read_fixed_opaque, read_var_array, read_var_opaque, read_var_string, XDRIn,
-};
-#[allow(unused_imports)]
-use xdr_rs_serialize::ser::{
-    write_fixed_array, write_fixed_opaque, write_var_array, write_var_opaque, write_var_string,
-    XDROut,
-};
-
+use xdr_rs_serialize::de::XDRIn;
+use xdr_rs_serialize::ser::XDROut;
 use xdr_rs_serialize::error::Error;
 
 // Simple xdr struct made up of a hyper and a u_hyper.
@@ -37,6 +29,7 @@ impl Default for SampleEnum {
 
 // Signature typedef as opaque[64]
 #[derive(Default, Debug, XDROut, XDRIn)]
+#[xdr(transparent)]
 pub struct Signature {
     #[array(fixed = 64)]
     pub t: Vec<u8>,
@@ -118,7 +111,7 @@ fn main() -> Result<(), Error> {
     // so fixed arrays must be manually initialized.
     to_xdr.fixed_opaque = vec![3; 12];
     to_xdr.fixed_xdr = vec![3; 12];
-    // Typedefs are wrapped in structs with the datatype at property t
+    // Typedefs are wrapped in transparent structs, encoded as their one field
     to_xdr.sig.t = vec![1; 64];
 
     // Write the xdr encoded bytes to a buffer.
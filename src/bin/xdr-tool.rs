@@ -0,0 +1,204 @@
+//! Converts XDR values to and from JSON using the types of a `.x` schema.
+//!
+//! ```text
+//! xdr-tool decode <schema.x> <type> [--format raw|hex|base64] [file]
+//! xdr-tool encode <schema.x> <type> [--format raw|hex|base64] [file]
+//! ```
+//!
+//! Input is read from `file` or stdin. `decode` reads XDR in the given format
+//! and prints JSON, `encode` reads JSON and prints XDR in the given format.
+
+extern crate base64;
+extern crate hex;
+extern crate json;
+extern crate xdr_rs_serialize;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+use xdr_rs_serialize::de::XDRReader;
+use xdr_rs_serialize::error::Error;
+use xdr_rs_serialize::runtime::{RuntimeSchema, TypeDesc};
+use xdr_rs_serialize::schema::Schema;
+
+const USAGE: &str =
+    "usage: xdr-tool <decode|encode> <schema.x> <type> [--format raw|hex|base64] [file]";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Raw,
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    encode: bool,
+    schema: String,
+    type_name: String,
+    format: Format,
+    input: Option<String>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let encode = match args.next().as_deref() {
+        Some("decode") => false,
+        Some("encode") => true,
+        _ => return Err(USAGE.to_string()),
+    };
+    let mut format = Format::Raw;
+    let mut positional = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            format = match args.next().as_deref() {
+                Some("raw") => Format::Raw,
+                Some("hex") => Format::Hex,
+                Some("base64") => Format::Base64,
+                Some(other) => return Err(format!("unknown format {}", other)),
+                None => return Err(USAGE.to_string()),
+            };
+        } else {
+            positional.push(arg);
+        }
+    }
+    if positional.len() < 2 || positional.len() > 3 {
+        return Err(USAGE.to_string());
+    }
+    let mut positional = positional.into_iter();
+    Ok(Args {
+        encode,
+        schema: positional.next().unwrap(),
+        type_name: positional.next().unwrap(),
+        format,
+        input: positional.next(),
+    })
+}
+
+fn read_input(input: &Option<String>) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    match input {
+        Some(path) => buffer = fs::read(path)?,
+        None => {
+            io::stdin().read_to_end(&mut buffer)?;
+        }
+    }
+    Ok(buffer)
+}
+
+fn decode_input(format: Format, input: Vec<u8>) -> Result<Vec<u8>, String> {
+    let text = |input: &[u8]| -> Result<String, String> {
+        let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+        Ok(text.split_whitespace().collect())
+    };
+    match format {
+        Format::Raw => Ok(input),
+        Format::Hex => hex::decode(text(&input)?).map_err(|e| format!("invalid hex input: {}", e)),
+        Format::Base64 => {
+            base64::decode(text(&input)?).map_err(|e| format!("invalid base64 input: {}", e))
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let source = fs::read_to_string(&args.schema).map_err(|e| format!("{}: {}", args.schema, e))?;
+    let schema = Schema::parse(&source)
+        .and_then(|schema| RuntimeSchema::new(&schema))
+        .map_err(|e| format!("{}: {}", args.schema, e))?;
+    if schema.get(&args.type_name).is_none() {
+        return Err(format!("unknown type {}", args.type_name));
+    }
+    let ty = TypeDesc::Named(args.type_name.clone());
+    let input = read_input(&args.input).map_err(|e| e.to_string())?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if args.encode {
+        let text = std::str::from_utf8(&input).map_err(|e| e.to_string())?;
+        let value = json::parse(text).map_err(|_| Error::invalid_json().to_string())?;
+        let mut xdr = Vec::new();
+        schema
+            .json_to_xdr(&ty, value, &mut xdr)
+            .map_err(|e| e.to_string())?;
+        let result = match args.format {
+            Format::Raw => out.write_all(&xdr),
+            Format::Hex => writeln!(out, "{}", hex::encode(&xdr)),
+            Format::Base64 => writeln!(out, "{}", base64::encode(&xdr)),
+        };
+        result.map_err(|e| e.to_string())
+    } else {
+        let xdr = decode_input(args.format, input)?;
+        let mut reader = XDRReader::new(&xdr[..]);
+        let mut json = Vec::new();
+        schema
+            .xdr_to_json(&ty, &mut reader, &mut json)
+            .and_then(|_| reader.expect_end())
+            .map_err(|e| e.to_string())?;
+        out.write_all(&json)
+            .and_then(|_| writeln!(out))
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1)).and_then(run);
+    if let Err(message) = result {
+        eprintln!("xdr-tool: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            Ok(Args {
+                encode: false,
+                schema: "a.x".to_string(),
+                type_name: "Item".to_string(),
+                format: Format::Raw,
+                input: None,
+            }),
+            args(&["decode", "a.x", "Item"])
+        );
+        assert_eq!(
+            Ok(Args {
+                encode: true,
+                schema: "a.x".to_string(),
+                type_name: "Item".to_string(),
+                format: Format::Base64,
+                input: Some("in.json".to_string()),
+            }),
+            args(&["encode", "a.x", "--format", "base64", "Item", "in.json"])
+        );
+        assert_eq!(
+            Err("unknown format bin".to_string()),
+            args(&["decode", "a.x", "Item", "--format", "bin"])
+        );
+        assert_eq!(Err(USAGE.to_string()), args(&["convert", "a.x", "Item"]));
+        assert_eq!(Err(USAGE.to_string()), args(&["decode", "a.x"]));
+    }
+
+    #[test]
+    fn test_decode_input() {
+        let input = |s: &str| s.as_bytes().to_vec();
+        assert_eq!(Ok(vec![32, 10]), decode_input(Format::Raw, input(" \n")));
+        assert_eq!(
+            Ok(vec![0, 0, 0, 1]),
+            decode_input(Format::Hex, input("0000\n 0001\n"))
+        );
+        assert_eq!(
+            Ok(vec![0, 0, 0, 1]),
+            decode_input(Format::Base64, input("AAAAAQ==\n"))
+        );
+        assert!(decode_input(Format::Hex, input("zz")).is_err());
+    }
+}
@@ -0,0 +1,740 @@
+use crate::error::Error;
+use crate::schema::{
+    Constants, Declaration, DeclarationKind, Definition, Schema, Type, Union, Value,
+};
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "// Generated by xdr-rs-serialize. Do not edit.
+
+#[allow(unused_imports)]
+use xdr_rs_serialize::types::Quad;
+use xdr_rs_serialize_derive::{XDRIn, XDROut};
+";
+
+const DERIVES: &str = "#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]";
+
+// Rust keywords that cannot be used as raw identifiers.
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Reads the `.x` file at `input` and writes the generated Rust types to
+/// `output`, for use from a build script:
+///
+/// ```no_run
+/// // In build.rs's main:
+/// let out = std::env::var("OUT_DIR").unwrap();
+/// println!("cargo:rerun-if-changed=types.x");
+/// xdr_rs_serialize::codegen::compile("types.x", format!("{}/types.rs", out)).unwrap();
+/// ```
+///
+/// The result is then pulled in with
+/// `include!(concat!(env!("OUT_DIR"), "/types.rs"));`.
+pub fn compile<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<(), Error> {
+    let source = fs::read_to_string(input)?;
+    let generated = generate(&Schema::parse(&source)?)?;
+    fs::write(output, generated)?;
+    Ok(())
+}
+
+/// Generates Rust types for `schema` that use the `XDROut` and `XDRIn`
+/// derives.
+///
+/// Constants become `i64` constants, typedefs of bounded arrays become
+/// newtypes and other typedefs become type aliases. Unions
+/// become enums with one variant per case label, named after the label when
+/// it is a constant and after the arm otherwise. Types declared inline are
+/// hoisted and named after their parent and field.
+pub fn generate(schema: &Schema) -> Result<String, Error> {
+    let mut generator = Generator {
+        schema,
+        constants: schema.constants()?,
+        items: Vec::new(),
+    };
+    for definition in schema.definitions.iter() {
+        generator.definition(definition)?;
+    }
+    let mut result = HEADER.to_string();
+    for item in generator.items {
+        result.push('\n');
+        result.push_str(&item);
+    }
+    Ok(result)
+}
+
+fn codegen_error(message: String) -> Error {
+    Error::invalid_schema(message)
+}
+
+fn rust_ident(name: &str) -> String {
+    if RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// `some_field` and `SOME_FIELD` become `SomeField`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            let rest: String = if part.chars().all(|c| !c.is_ascii_lowercase()) {
+                chars.as_str().to_ascii_lowercase()
+            } else {
+                chars.as_str().to_string()
+            };
+            format!("{}{}", first, rest)
+        })
+        .collect()
+}
+
+fn is_camel_case(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_uppercase()) && !name.contains('_')
+}
+
+fn is_snake_case(name: &str) -> bool {
+    !name.chars().any(|c| c.is_ascii_uppercase())
+}
+
+fn is_upper_case(name: &str) -> bool {
+    !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+// Clippy reads all-caps type and variant names, common in `.x` files, as
+// acronyms.
+fn is_acronym(name: &str) -> bool {
+    name.len() > 1 && name.chars().all(|c| c.is_ascii_uppercase())
+}
+
+struct Generator<'a> {
+    schema: &'a Schema,
+    constants: Constants,
+    items: Vec<String>,
+}
+
+// What a union switches on, which decides how its cases are given to the
+// derive's `#[discriminant]` attribute.
+enum Switch {
+    Int,
+    Unsigned,
+    Bool,
+    /// The Rust name of an enum and the values of its constants.
+    Enum(String, Vec<(String, i32)>),
+}
+
+impl<'a> Generator<'a> {
+    fn value(&self, value: &Value) -> Result<i64, Error> {
+        self.constants.value(value)
+    }
+
+    fn size(&self, value: &Value) -> Result<u32, Error> {
+        self.constants.size(value)
+    }
+
+    fn definition(&mut self, definition: &Definition) -> Result<(), Error> {
+        match definition {
+            Definition::Const(name, value) => {
+                // Schemas declare constants that a consumer may never use.
+                let allow = if is_upper_case(name) {
+                    "dead_code"
+                } else {
+                    "dead_code, non_upper_case_globals"
+                };
+                self.items.push(format!(
+                    "#[allow({})]\npub const {}: i64 = {};\n",
+                    allow,
+                    rust_ident(name),
+                    value
+                ));
+                Ok(())
+            }
+            Definition::Typedef(declaration) => self.typedef(declaration),
+            Definition::Type(name, ty) => self.named_type(name, ty),
+        }
+    }
+
+    fn typedef(&mut self, declaration: &Declaration) -> Result<(), Error> {
+        let name = &declaration.name;
+        match (&declaration.ty, &declaration.kind) {
+            (Type::Enum(_), DeclarationKind::Single)
+            | (Type::Struct(_), DeclarationKind::Single)
+            | (Type::Union(_), DeclarationKind::Single) => {
+                return self.named_type(name, &declaration.ty)
+            }
+            _ => {}
+        }
+        let (attribute, rust_type) = self.field_type(declaration, name)?;
+        match attribute {
+            Some(attribute) => self.wrapper(name, &attribute, &rust_type),
+            None => self
+                .items
+                .push(format!("pub type {} = {};\n", rust_ident(name), rust_type)),
+        }
+        Ok(())
+    }
+
+    // Bounded arrays need their `#[array]` attribute, so they are wrapped in
+    // a transparent newtype, which the derives encode as just its member.
+    fn wrapper(&mut self, name: &str, attribute: &str, rust_type: &str) {
+        self.items.push(format!(
+            "{}\n#[xdr(transparent)]\npub struct {}({} pub {});\n",
+            DERIVES,
+            rust_ident(name),
+            attribute,
+            rust_type
+        ));
+    }
+
+    fn named_type(&mut self, name: &str, ty: &Type) -> Result<(), Error> {
+        match ty {
+            Type::Enum(constants) => self.enumeration(name, constants),
+            Type::Struct(members) => self.structure(name, members),
+            Type::Union(union) => self.union(name, union),
+            _ => unreachable!("only enums, structs and unions are named types"),
+        }
+    }
+
+    fn enumeration(
+        &mut self,
+        name: &str,
+        constants: &[(String, Option<Value>)],
+    ) -> Result<(), Error> {
+        let values = self.constants.enum_values(constants)?;
+        let mut item = String::new();
+        if !values.iter().all(|(c, _)| is_camel_case(c)) || !is_camel_case(name) {
+            item.push_str("#[allow(non_camel_case_types)]\n");
+        }
+        if is_acronym(name) || values.iter().any(|(c, _)| is_acronym(c)) {
+            item.push_str("#[allow(clippy::upper_case_acronyms)]\n");
+        }
+        item.push_str(&format!("{}\npub enum {} {{\n", DERIVES, rust_ident(name)));
+        for (constant, value) in values.iter() {
+            item.push_str(&format!("    {} = {},\n", rust_ident(constant), value));
+        }
+        item.push_str("}\n");
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn structure(&mut self, name: &str, members: &[Declaration]) -> Result<(), Error> {
+        // Keep the struct ahead of any types hoisted out of its members.
+        let slot = self.items.len();
+        self.items.push(String::new());
+        let mut fields = String::new();
+        for member in members.iter() {
+            let inline_name = format!("{}{}", name, camel_case(&member.name));
+            let (attribute, rust_type) = self.field_type(member, &inline_name)?;
+            if let Some(attribute) = attribute {
+                fields.push_str(&format!("    {}\n", attribute));
+            }
+            fields.push_str(&format!(
+                "    pub {}: {},\n",
+                rust_ident(&member.name),
+                rust_type
+            ));
+        }
+        let mut item = String::new();
+        if !is_camel_case(name) {
+            item.push_str("#[allow(non_camel_case_types)]\n");
+        }
+        if is_acronym(name) {
+            item.push_str("#[allow(clippy::upper_case_acronyms)]\n");
+        }
+        if !members.iter().all(|m| is_snake_case(&m.name)) {
+            item.push_str("#[allow(non_snake_case)]\n");
+        }
+        item.push_str(&format!(
+            "{}\npub struct {} {{\n{}}}\n",
+            DERIVES,
+            rust_ident(name),
+            fields
+        ));
+        self.items[slot] = item;
+        Ok(())
+    }
+
+    // Typedefs are followed to the type they name.
+    fn switch(&self, name: &str, ty: &Type) -> Result<Option<Switch>, Error> {
+        Ok(match ty {
+            Type::Int => Some(Switch::Int),
+            Type::UnsignedInt => Some(Switch::Unsigned),
+            Type::Bool => Some(Switch::Bool),
+            Type::Enum(constants) => Some(Switch::Enum(
+                rust_ident(name),
+                self.constants.enum_values(constants)?,
+            )),
+            Type::Named(other) => match self.schema.definition(other) {
+                Some(Definition::Type(_, ty)) => self.switch(other, ty)?,
+                Some(Definition::Typedef(declaration))
+                    if declaration.kind == DeclarationKind::Single =>
+                {
+                    self.switch(other, &declaration.ty)?
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn union(&mut self, name: &str, union: &Union) -> Result<(), Error> {
+        let inline_name = format!("{}{}", name, camel_case(&union.discriminant.name));
+        let switch = self
+            .switch(&inline_name, &union.discriminant.ty)?
+            .ok_or_else(|| {
+                codegen_error(format!(
+                    "union {} must switch on an int, unsigned int, bool or enum",
+                    name
+                ))
+            })?;
+        if let Type::Enum(_) = union.discriminant.ty {
+            self.named_type(&inline_name, &union.discriminant.ty)?;
+        }
+
+        let slot = self.items.len();
+        self.items.push(String::new());
+        let mut variants = Vec::new();
+        for arm in union.arms.iter() {
+            let declaration = &arm.declaration;
+            let rust_type = self.arm_type(name, declaration)?;
+            for case in arm.cases.iter() {
+                let value = self.value(case)?;
+                let range = match switch {
+                    Switch::Unsigned => 0..=u32::MAX as i64,
+                    _ => i32::MIN as i64..=i32::MAX as i64,
+                };
+                if !range.contains(&value) {
+                    return Err(codegen_error(format!(
+                        "case {} of union {} is out of range",
+                        value, name
+                    )));
+                }
+                let attribute = match switch {
+                    Switch::Int => format!("value = \"{}\"", value),
+                    Switch::Unsigned => format!("unsigned = \"{}\"", value),
+                    Switch::Bool if value == 0 || value == 1 => {
+                        format!("bool = \"{}\"", value == 1)
+                    }
+                    Switch::Bool => {
+                        return Err(codegen_error(format!(
+                            "case {} of union {} is not a bool",
+                            value, name
+                        )))
+                    }
+                    Switch::Enum(ref enum_name, ref values) => {
+                        match values.iter().find(|(_, v)| *v as i64 == value) {
+                            Some((constant, _)) => {
+                                format!("enum = \"{}::{}\"", enum_name, rust_ident(constant))
+                            }
+                            None => {
+                                return Err(codegen_error(format!(
+                                    "case {} of union {} is not a value of {}",
+                                    value, name, enum_name
+                                )))
+                            }
+                        }
+                    }
+                };
+                let variant = match case {
+                    Value::Named(label) => label.clone(),
+                    Value::Constant(_) if arm.cases.len() == 1 && !declaration.name.is_empty() => {
+                        declaration.name.clone()
+                    }
+                    Value::Constant(n) => {
+                        let base = if declaration.name.is_empty() {
+                            "V"
+                        } else {
+                            &declaration.name
+                        };
+                        match *n < 0 {
+                            true => format!("{}Neg{}", base, -n),
+                            false => format!("{}{}", base, n),
+                        }
+                    }
+                };
+                if variants.iter().any(|(v, _, _)| v == &variant) {
+                    return Err(codegen_error(format!(
+                        "union {} has more than one variant named {}",
+                        name, variant
+                    )));
+                }
+                variants.push((variant, attribute, rust_type.clone()));
+            }
+        }
+
+        // The default variant keeps the discriminant it was read with.
+        let default = match &union.default {
+            Some(declaration) => {
+                let variant = match declaration.name.is_empty() {
+                    true => "Default".to_string(),
+                    false => declaration.name.clone(),
+                };
+                if variants.iter().any(|(v, _, _)| v == &variant) {
+                    return Err(codegen_error(format!(
+                        "union {} has more than one variant named {}",
+                        name, variant
+                    )));
+                }
+                let discriminant = match switch {
+                    Switch::Int => "i32",
+                    Switch::Unsigned => "u32",
+                    Switch::Bool => "bool",
+                    Switch::Enum(ref enum_name, _) => enum_name,
+                };
+                let fields = match declaration.ty {
+                    Type::Void => discriminant.to_string(),
+                    _ => format!("{}, {}", discriminant, self.arm_type(name, declaration)?),
+                };
+                Some((variant, fields))
+            }
+            None => None,
+        };
+
+        let mut item = String::new();
+        if !is_camel_case(name)
+            || !variants.iter().all(|(v, _, _)| is_camel_case(v))
+            || !default.iter().all(|(v, _)| is_camel_case(v))
+        {
+            item.push_str("#[allow(non_camel_case_types)]\n");
+        }
+        if is_acronym(name)
+            || variants.iter().any(|(v, _, _)| is_acronym(v))
+            || default.iter().any(|(v, _)| is_acronym(v))
+        {
+            item.push_str("#[allow(clippy::upper_case_acronyms)]\n");
+        }
+        item.push_str(&format!("{}\npub enum {} {{\n", DERIVES, rust_ident(name)));
+        for (variant, attribute, rust_type) in variants.iter() {
+            item.push_str(&format!(
+                "    #[discriminant({})]\n    {}({}),\n",
+                attribute,
+                rust_ident(variant),
+                rust_type
+            ));
+        }
+        if let Some((variant, fields)) = default {
+            item.push_str(&format!(
+                "    #[discriminant(default)]\n    {}({}),\n",
+                rust_ident(&variant),
+                fields
+            ));
+        }
+        item.push_str("}\n");
+        self.items[slot] = item;
+        Ok(())
+    }
+
+    // The Rust type of a union arm. Arms with an `#[array]` attribute are
+    // wrapped, as variants cannot carry attributes on their field.
+    fn arm_type(&mut self, name: &str, declaration: &Declaration) -> Result<String, Error> {
+        if declaration.ty == Type::Void {
+            return Ok("()".to_string());
+        }
+        let inline_name = format!("{}{}", name, camel_case(&declaration.name));
+        Ok(match self.field_type(declaration, &inline_name)? {
+            (Some(attribute), rust_type) => {
+                self.wrapper(&inline_name, &attribute, &rust_type);
+                inline_name
+            }
+            (None, rust_type) => rust_type,
+        })
+    }
+
+    // The Rust type of a declaration, with the `#[array]` attribute it needs
+    // if any. Inline types are generated under `inline_name`.
+    fn field_type(
+        &mut self,
+        declaration: &Declaration,
+        inline_name: &str,
+    ) -> Result<(Option<String>, String), Error> {
+        let fixed = |size: u32| Some(format!("#[array(fixed = {})]", size));
+        let var = |max: u32| Some(format!("#[array(var = {})]", max));
+        Ok(match (&declaration.ty, &declaration.kind) {
+            (Type::Opaque, DeclarationKind::FixedArray(size)) => {
+                (fixed(self.size(size)?), "Vec<u8>".to_string())
+            }
+            (Type::Opaque, DeclarationKind::VarArray(Some(max))) => {
+                (var(self.size(max)?), "Vec<u8>".to_string())
+            }
+            (Type::Opaque, _) => (None, "Vec<u8>".to_string()),
+            (Type::String, DeclarationKind::VarArray(Some(max))) => {
+                (var(self.size(max)?), "String".to_string())
+            }
+            (Type::String, _) => (None, "String".to_string()),
+            (ty, DeclarationKind::Single) => (None, self.base_type(ty, inline_name)?),
+            (ty, DeclarationKind::FixedArray(size)) => (
+                fixed(self.size(size)?),
+                format!("Vec<{}>", self.base_type(ty, inline_name)?),
+            ),
+            (ty, DeclarationKind::VarArray(Some(max))) => (
+                var(self.size(max)?),
+                format!("Vec<{}>", self.base_type(ty, inline_name)?),
+            ),
+            (ty, DeclarationKind::VarArray(None)) => {
+                (None, format!("Vec<{}>", self.base_type(ty, inline_name)?))
+            }
+            (ty, DeclarationKind::Optional) => {
+                let base = self.base_type(ty, inline_name)?;
+                // Boxed so that optional data can refer back to its own type.
+                if is_inline(ty) || matches!(ty, Type::Named(_)) {
+                    (None, format!("Option<Box<{}>>", base))
+                } else {
+                    (None, format!("Option<{}>", base))
+                }
+            }
+        })
+    }
+
+    fn base_type(&mut self, ty: &Type, inline_name: &str) -> Result<String, Error> {
+        Ok(match ty {
+            Type::Int => "i32".to_string(),
+            Type::UnsignedInt => "u32".to_string(),
+            Type::Hyper => "i64".to_string(),
+            Type::UnsignedHyper => "u64".to_string(),
+            Type::Float => "f32".to_string(),
+            Type::Double => "f64".to_string(),
+            Type::Quadruple => "Quad".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::Void => "()".to_string(),
+            Type::Opaque | Type::String => {
+                return Err(codegen_error(format!(
+                    "{} must be declared as an array",
+                    inline_name
+                )))
+            }
+            Type::Named(name) => rust_ident(name),
+            Type::Enum(_) | Type::Struct(_) | Type::Union(_) => {
+                self.named_type(inline_name, ty)?;
+                rust_ident(inline_name)
+            }
+        })
+    }
+}
+
+fn is_inline(ty: &Type) -> bool {
+    matches!(ty, Type::Enum(_) | Type::Struct(_) | Type::Union(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    fn generate_items(source: &str) -> String {
+        let generated = generate(&Schema::parse(source).unwrap()).unwrap();
+        generated[HEADER.len()..].to_string()
+    }
+
+    fn assert_codegen_error(source: &str, message: &str) {
+        match generate(&Schema::parse(source).unwrap()) {
+            Err(e) => assert_eq!(&ErrorKind::InvalidSchema(message.to_string()), e.kind()),
+            Ok(generated) => panic!("generated {}", generated),
+        }
+    }
+
+    #[test]
+    fn test_generate_typedefs() {
+        let expected = r#"
+#[allow(dead_code)]
+pub const SIZE: i64 = 4;
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct Hash(#[array(fixed = 4)] pub Vec<u8>);
+
+pub type Name = String;
+
+pub type Next = Option<Box<Hash>>;
+
+pub type Count = u32;
+"#;
+        assert_eq!(
+            expected,
+            generate_items(
+                "const SIZE = 4;
+                 typedef opaque Hash[SIZE];
+                 typedef string Name<>;
+                 typedef Hash *Next;
+                 typedef unsigned Count;"
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_struct() {
+        let expected = r#"
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub struct Account {
+    pub r#type: i32,
+    pub balanceLow: u64,
+    #[array(var = 3)]
+    pub signers: Vec<AccountSigners>,
+    pub next: Option<Box<Account>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub struct AccountSigners {
+    pub weight: f32,
+    pub level: AccountSignersLevel,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum AccountSignersLevel {
+    LOW_LEVEL = -1,
+    HIGH_LEVEL = 0,
+}
+"#;
+        assert_eq!(
+            expected,
+            generate_items(
+                "struct Account {
+                     int type;
+                     unsigned hyper balanceLow;
+                     struct {
+                         float weight;
+                         enum { LOW_LEVEL = -1, HIGH_LEVEL } level;
+                     } signers<3>;
+                     Account *next;
+                     hyper *limit;
+                 };"
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_union() {
+        let expected = r#"
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Kind {
+    Small = 0,
+    Large = 1,
+    Empty = 2,
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Body {
+    #[discriminant(enum = "Kind::Small")]
+    Small(i32),
+    #[discriminant(enum = "Kind::Large")]
+    Large(BodyData),
+    #[discriminant(enum = "Kind::Empty")]
+    Empty(()),
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct BodyData(#[array(var = 10)] pub Vec<u8>);
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Ext {
+    #[discriminant(unsigned = "0")]
+    V0(()),
+    #[discriminant(unsigned = "4294967295")]
+    code4294967295(i32),
+    #[discriminant(unsigned = "2147483648")]
+    code2147483648(i32),
+    #[discriminant(default)]
+    Default(u32),
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Reply {
+    #[discriminant(value = "0")]
+    V0(()),
+    #[discriminant(default)]
+    message(i32, ReplyMessage),
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct ReplyMessage(#[array(var = 64)] pub String);
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Flag {
+    #[discriminant(bool = "true")]
+    TRUE(i32),
+    #[discriminant(bool = "false")]
+    FALSE(()),
+}
+"#;
+        assert_eq!(
+            expected,
+            generate_items(
+                "enum Kind { Small, Large, Empty };
+                 union Body switch (Kind k) {
+                     case Small: int value;
+                     case Large: opaque data<10>;
+                     case Empty: void;
+                 };
+                 union Ext switch (unsigned int v) {
+                     case 0: void;
+                     case 0xFFFFFFFF: case 0x80000000: int code;
+                     default: void;
+                 };
+                 union Reply switch (int code) {
+                     case 0: void;
+                     default: string message<64>;
+                 };
+                 union Flag switch (bool set) {
+                     case TRUE: int count;
+                     case FALSE: void;
+                 };"
+            )
+        );
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        assert_codegen_error("typedef opaque Hash[SIZE];", "unknown constant SIZE");
+        assert_codegen_error("typedef int Big[-1];", "invalid array size -1");
+        assert_codegen_error(
+            "enum Big { HUGE = 0x80000000 };",
+            "enum value 2147483648 of HUGE is out of range",
+        );
+        assert_codegen_error(
+            "union U switch (int d) { case 0: int Default; default: void; };",
+            "union U has more than one variant named Default",
+        );
+        assert_codegen_error(
+            "union U switch (hyper d) { case 0: void; };",
+            "union U must switch on an int, unsigned int, bool or enum",
+        );
+        assert_codegen_error(
+            "union U switch (int d) { case 0x80000000: void; };",
+            "case 2147483648 of union U is out of range",
+        );
+        assert_codegen_error(
+            "union U switch (unsigned int d) { case -1: void; };",
+            "case -1 of union U is out of range",
+        );
+        assert_codegen_error(
+            "union U switch (bool b) { case 2: void; };",
+            "case 2 of union U is not a bool",
+        );
+        assert_codegen_error(
+            "enum Kind { A, B }; union U switch (Kind k) { case 5: void; };",
+            "case 5 of union U is not a value of Kind",
+        );
+        assert_codegen_error(
+            "union U switch (int d) { case 1: case 2: void; case 3: int V1; };",
+            "union U has more than one variant named V1",
+        );
+    }
+}
@@ -3,23 +3,15 @@ extern crate hex;
 extern crate json;
 
 use crate::error::Error;
+use crate::types::{BoundedString, Quad, VarArray, VarOpaque};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+pub use std::io::Read;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use json::JsonValue;
 
-macro_rules! arr4 {
-    ($s:ident) => {
-        [$s[0], $s[1], $s[2], $s[3]]
-    };
-}
-
-macro_rules! arr8 {
-    ($s:ident) => {
-        [$s[0], $s[1], $s[2], $s[3], $s[4], $s[5], $s[6], $s[7]]
-    };
-}
-
 pub fn read_json_string<T: XDRIn>(json_str: String) -> Result<T, Error> {
     match json::parse(&json_str) {
         Ok(res) => T::read_json(res),
@@ -27,17 +19,282 @@ pub fn read_json_string<T: XDRIn>(json_str: String) -> Result<T, Error> {
     }
 }
 
+/// Decodes a `T` from the start of `bytes`, ignoring anything after it.
+pub fn from_xdr_bytes<T: XDRIn>(bytes: &[u8]) -> Result<T, Error> {
+    Ok(T::read_xdr(bytes)?.0)
+}
+
+/// Decodes a `T` that must use all of `bytes`, failing with `TrailingBytes`
+/// otherwise.
+pub fn from_xdr_exact<T: XDRIn>(bytes: &[u8]) -> Result<T, Error> {
+    let mut reader = XDRReader::new(bytes);
+    let result = T::read_xdr_from(&mut reader)?.0;
+    reader.expect_end()?;
+    Ok(result)
+}
+
+/// Bounds applied while decoding, so that untrusted input cannot make the
+/// decoder allocate or recurse without limit.
+///
+/// `max_bytes` caps the total memory requested for opaque data, strings,
+/// arrays and boxed values, `max_elements` caps the total number of array
+/// elements decoded and `max_depth` caps the nesting of arrays, optionals and
+/// boxed values, the only places a type can recurse.
+///
+/// The defaults allow 64 MiB, 4Mi elements and 512 levels, so that a hostile
+/// length prefix fails up front even for elements that take no input, like
+/// `()`. Larger values need larger limits, or `DecodeLimits::unbounded()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeLimits {
+    pub max_bytes: u64,
+    pub max_elements: u64,
+    pub max_depth: u32,
+}
+
+impl DecodeLimits {
+    /// Limits that never fail on size, for trusted input. Depth stays
+    /// bounded to protect the stack.
+    pub fn unbounded() -> Self {
+        DecodeLimits {
+            max_bytes: u64::MAX,
+            max_elements: u64::MAX,
+            ..DecodeLimits::default()
+        }
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_bytes: 64 << 20,
+            max_elements: 4 << 20,
+            max_depth: 512,
+        }
+    }
+}
+
+/// Source of XDR encoded bytes for the streaming decode path.
+///
+/// Wraps any `std::io::Read` and keeps count of the bytes consumed from it,
+/// so values can be pulled incrementally from files or sockets instead of
+/// being buffered up front. The reader also carries the `DecodeLimits` for
+/// the value being decoded, and whether padding must be canonical.
+pub struct XDRReader<R: Read> {
+    inner: R,
+    position: u64,
+    limits: DecodeLimits,
+    strict: bool,
+    allocated: u64,
+    elements: u64,
+    depth: u32,
+}
+
+impl<R: Read> XDRReader<R> {
+    pub fn new(inner: R) -> Self {
+        XDRReader::with_limits(inner, DecodeLimits::default())
+    }
+
+    pub fn with_limits(inner: R, limits: DecodeLimits) -> Self {
+        XDRReader {
+            inner,
+            position: 0,
+            limits,
+            strict: false,
+            allocated: 0,
+            elements: 0,
+            depth: 0,
+        }
+    }
+
+    /// Number of bytes consumed from the underlying reader so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Enables strict decoding, where padding bytes must be zero as RFC 4506
+    /// requires. Non-zero padding then fails with `InvalidPadding` instead of
+    /// being skipped.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Checks that the underlying reader is exhausted, failing with
+    /// `TrailingBytes` when anything follows the value decoded so far.
+    pub fn expect_end(&mut self) -> Result<(), Error> {
+        let mut byte = [0; 1];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(()),
+                Ok(_) => return Err(Error::trailing_bytes().at_offset(self.position)),
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::from(e).at_offset(self.position)),
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Charges `bytes` against the allocation budget before allocating them.
+    pub fn reserve_bytes(&mut self, bytes: u64) -> Result<(), Error> {
+        match self.allocated.checked_add(bytes) {
+            Some(total) if total <= self.limits.max_bytes => {
+                self.allocated = total;
+                Ok(())
+            }
+            _ => Err(Error::allocation_limit_exceeded().at_offset(self.position)),
+        }
+    }
+
+    /// Charges `count` array elements against the element budget.
+    pub fn reserve_elements(&mut self, count: u64) -> Result<(), Error> {
+        match self.elements.checked_add(count) {
+            Some(total) if total <= self.limits.max_elements => {
+                self.elements = total;
+                Ok(())
+            }
+            _ => Err(Error::element_limit_exceeded().at_offset(self.position)),
+        }
+    }
+
+    /// Runs `decode` one nesting level deeper, failing once the depth limit
+    /// is reached.
+    pub fn nested<T, F>(&mut self, decode: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        if self.depth >= self.limits.max_depth {
+            return Err(Error::depth_limit_exceeded().at_offset(self.position));
+        }
+        self.depth += 1;
+        let result = decode(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Runs `decode` for the struct field `name`, adding the field and its
+    /// starting offset to any error it returns.
+    pub fn field<T, F>(&mut self, name: &str, decode: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        let start = self.position;
+        decode(self).map_err(|e| e.at_offset(start).in_field(name))
+    }
+
+    /// Runs `decode` for the array element at `index`, adding the index and
+    /// its starting offset to any error it returns.
+    pub fn element<T, F>(&mut self, index: usize, decode: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        let start = self.position;
+        decode(self).map_err(|e| e.at_offset(start).at_index(index))
+    }
+}
+
+impl<'a> XDRReader<&'a [u8]> {
+    /// Takes the next `len` bytes of the buffer without copying them.
+    pub fn borrow_bytes(&mut self, len: u32) -> Result<&'a [u8], Error> {
+        let len = len as usize;
+        if self.inner.len() < len {
+            let end = self.position + self.inner.len() as u64;
+            return Err(Error::unexpected_eof().at_offset(end));
+        }
+        let (bytes, rest) = self.inner.split_at(len);
+        self.inner = rest;
+        self.position += len as u64;
+        Ok(bytes)
+    }
+}
+
+impl<R: Read> Read for XDRReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+// Fills `buf` from the reader, reporting input that ends early as
+// `UnexpectedEof` rather than as an IO error.
+fn read_into<R: Read>(reader: &mut XDRReader<R>, buf: &mut [u8]) -> Result<(), Error> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(()),
+        Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Err(Error::unexpected_eof().at_offset(reader.position()))
+        }
+        Err(e) => Err(Error::from(e).at_offset(reader.position())),
+    }
+}
+
+// Reads `len` bytes without trusting `len` for the allocation size up front.
+fn read_opaque_body<R: Read>(reader: &mut XDRReader<R>, len: u32) -> Result<Vec<u8>, Error> {
+    reader.reserve_bytes(len as u64)?;
+    let mut result = Vec::new();
+    if let Err(e) = reader.by_ref().take(len as u64).read_to_end(&mut result) {
+        return Err(Error::from(e).at_offset(reader.position()));
+    }
+    if result.len() != len as usize {
+        return Err(Error::unexpected_eof().at_offset(reader.position()));
+    }
+    Ok(result)
+}
+
+// Consumes the padding following `len` bytes of opaque data, checking that
+// it is zero when the reader is strict.
+fn read_padding<R: Read>(reader: &mut XDRReader<R>, len: u64) -> Result<u64, Error> {
+    let padding = ((4 - len % 4) % 4) as usize;
+    let start = reader.position();
+    let mut buf = [0; 3];
+    read_into(reader, &mut buf[..padding])?;
+    if reader.is_strict() && buf.iter().any(|&b| b != 0) {
+        return Err(Error::invalid_padding().at_offset(start));
+    }
+    Ok(padding as u64)
+}
+
+fn read_string_body<R: Read>(reader: &mut XDRReader<R>, len: u32) -> Result<(String, u64), Error> {
+    let start = reader.position();
+    let bytes = read_opaque_body(reader, len)?;
+    let result =
+        String::from_utf8(bytes).map_err(|e| Error::from(e.utf8_error()).at_offset(start))?;
+    let read = len as u64 + read_padding(reader, len as u64)?;
+    Ok((result, read))
+}
+
 pub trait XDRIn: Sized {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error>;
+    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
+        Self::read_xdr_from(&mut XDRReader::new(buffer))
+    }
+    /// Decodes `buffer` in strict mode: padding must be zero and the value
+    /// must use the whole buffer.
+    fn read_xdr_strict(buffer: &[u8]) -> Result<(Self, u64), Error> {
+        let mut reader = XDRReader::new(buffer).strict();
+        let result = Self::read_xdr_from(&mut reader)?;
+        reader.expect_end()?;
+        Ok(result)
+    }
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error>;
     fn read_json(jval: json::JsonValue) -> Result<Self, Error>;
 }
 
 impl XDRIn for () {
-    fn read_xdr(_buffer: &[u8]) -> Result<(Self, u64), Error> {
+    fn read_xdr_from<R: Read>(_reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
         Ok(((), 0))
     }
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
-        if jval.is_string() && jval == "" {
+        if jval.is_string() && jval.is_empty() {
             return Ok(());
         }
         Err(Error::invalid_json())
@@ -45,11 +302,11 @@ impl XDRIn for () {
 }
 
 impl XDRIn for bool {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        match i32::read_xdr(buffer) {
-            Ok((1, 4)) => Ok((true, 4)),
-            Ok((0, 4)) => Ok((false, 4)),
-            _ => Err(Error::bool_bad_format()),
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        match i32::read_xdr_from(reader)? {
+            (1, 4) => Ok((true, 4)),
+            (0, 4) => Ok((false, 4)),
+            _ => Err(Error::bool_bad_format().at_offset(reader.position() - 4)),
         }
     }
 
@@ -62,12 +319,10 @@ impl XDRIn for bool {
 }
 
 impl XDRIn for i32 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 4 {
-            return Err(Error::integer_bad_format());
-        }
-        let result = i32::from_be_bytes(arr4!(buffer));
-        Ok((result, 4))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 4];
+        read_into(reader, &mut bytes)?;
+        Ok((i32::from_be_bytes(bytes), 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -79,12 +334,10 @@ impl XDRIn for i32 {
 }
 
 impl XDRIn for u32 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 4 {
-            return Err(Error::unsigned_integer_bad_format());
-        }
-        let result = u32::from_be_bytes(arr4!(buffer));
-        Ok((result, 4))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 4];
+        read_into(reader, &mut bytes)?;
+        Ok((u32::from_be_bytes(bytes), 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -96,12 +349,10 @@ impl XDRIn for u32 {
 }
 
 impl XDRIn for i64 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 8 {
-            return Err(Error::hyper_bad_format());
-        }
-        let result = i64::from_be_bytes(arr8!(buffer));
-        Ok((result, 8))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 8];
+        read_into(reader, &mut bytes)?;
+        Ok((i64::from_be_bytes(bytes), 8))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -115,12 +366,10 @@ impl XDRIn for i64 {
 }
 
 impl XDRIn for u64 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 8 {
-            return Err(Error::unsigned_hyper_bad_format());
-        }
-        let result = u64::from_be_bytes(arr8!(buffer));
-        Ok((result, 8))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 8];
+        read_into(reader, &mut bytes)?;
+        Ok((u64::from_be_bytes(bytes), 8))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -134,12 +383,10 @@ impl XDRIn for u64 {
 }
 
 impl XDRIn for f32 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 4 {
-            return Err(Error::float_bad_format());
-        }
-        let result = f32::from_bits(u32::from_be_bytes(arr4!(buffer)));
-        Ok((result, 4))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 4];
+        read_into(reader, &mut bytes)?;
+        Ok((f32::from_bits(u32::from_be_bytes(bytes)), 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -151,12 +398,10 @@ impl XDRIn for f32 {
 }
 
 impl XDRIn for f64 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        if buffer.len() < 8 {
-            return Err(Error::double_bad_format());
-        }
-        let result = f64::from_bits(u64::from_be_bytes(arr8!(buffer)));
-        Ok((result, 8))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 8];
+        read_into(reader, &mut bytes)?;
+        Ok((f64::from_bits(u64::from_be_bytes(bytes)), 8))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -167,17 +412,33 @@ impl XDRIn for f64 {
     }
 }
 
-impl XDRIn for String {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let size = u32::read_xdr(buffer)?.0;
-        let len = size as usize;
-        let mut read: u64 = 4;
-        if buffer.len() < len {
-            return Err(Error::string_bad_format());
+impl XDRIn for Quad {
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut bytes = [0; 16];
+        read_into(reader, &mut bytes)?;
+        Ok((Quad::from_bits(u128::from_be_bytes(bytes)), 16))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        match jval {
+            JsonValue::Number(num) => Ok(Quad::from(f64::from(num))),
+            JsonValue::Short(_) | JsonValue::String(_) => {
+                let mut bytes = [0; 16];
+                match hex::decode_to_slice(jval.to_string(), &mut bytes) {
+                    Ok(()) => Ok(Quad::from_bits(u128::from_be_bytes(bytes))),
+                    Err(_) => Err(Error::quad_bad_format()),
+                }
+            }
+            _ => Err(Error::quad_bad_format()),
         }
-        let result = std::str::from_utf8(&buffer[4..len + 4])?;
-        read += size as u64;
-        Ok((result.to_string(), read + (4 - read % 4) % 4))
+    }
+}
+
+impl XDRIn for String {
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let size = u32::read_xdr_from(reader)?.0;
+        let (result, read) = read_string_body(reader, size)?;
+        Ok((result, read + 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -192,16 +453,10 @@ impl<T> XDRIn for Vec<T>
 where
     T: XDRIn,
 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let size = u32::read_xdr(buffer)?.0;
-        let mut read: u64 = 4;
-        let mut result = Vec::new();
-        for _ in 0..size {
-            let t_read = T::read_xdr(&buffer[read as usize..])?;
-            read += t_read.1;
-            result.push(t_read.0);
-        }
-        Ok((result, read))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let size = u32::read_xdr_from(reader)?.0;
+        let result = read_fixed_array(size, reader)?;
+        Ok((result.0, result.1 + 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -215,8 +470,8 @@ where
 
         match jval {
             JsonValue::Array(vals) => {
-                for val in vals {
-                    result.push(T::read_json(val)?);
+                for (index, val) in vals.into_iter().enumerate() {
+                    result.push(T::read_json(val).map_err(|e| e.at_index(index))?);
                 }
             }
             JsonValue::Null => return Ok(result), // parse null array as empty
@@ -227,13 +482,10 @@ where
 }
 
 impl XDRIn for Vec<u8> {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let len = u32::read_xdr(buffer)?.0;
-        let size = len as usize;
-        let mut read: u64 = 4;
-        let result = buffer[4..size + 4].to_vec();
-        read += size as u64;
-        Ok((result, read + (4 - read % 4) % 4))
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let len = u32::read_xdr_from(reader)?.0;
+        let result = read_fixed_opaque(len, reader)?;
+        Ok((result.0, result.1 + 4))
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
@@ -247,34 +499,109 @@ impl XDRIn for Vec<u8> {
     }
 }
 
+impl<T, const N: usize> XDRIn for [T; N]
+where
+    T: XDRIn,
+{
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let (result, read) = read_fixed_array(N as u32, reader)?;
+        let result = <[T; N]>::try_from(result).map_err(|_| Error::bad_array_size())?;
+        Ok((result, read))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        let result = Vec::read_json(jval)?;
+        <[T; N]>::try_from(result).map_err(|_| Error::bad_array_size())
+    }
+}
+
+impl<const N: usize> XDRIn for [u8; N] {
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let mut result = [0; N];
+        read_into(reader, &mut result)?;
+        let padding = read_padding(reader, N as u64)?;
+        Ok((result, N as u64 + padding))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        let result = read_fixed_opaque_json(N as u32, jval)?;
+        <[u8; N]>::try_from(result).map_err(|_| Error::bad_array_size())
+    }
+}
+
+impl<const MAX: u32> XDRIn for VarOpaque<MAX> {
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let (result, read) = read_var_opaque(MAX, reader)?;
+        Ok((VarOpaque::new(result)?, read))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        VarOpaque::new(read_var_opaque_json(MAX, jval)?)
+    }
+}
+
+impl<T, const MAX: u32> XDRIn for VarArray<T, MAX>
+where
+    T: XDRIn,
+{
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let (result, read) = read_var_array(MAX, reader)?;
+        Ok((VarArray::new(result)?, read))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        VarArray::new(read_var_array_json(MAX, jval)?)
+    }
+}
+
+impl<const MAX: u32> XDRIn for BoundedString<MAX> {
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        let (result, read) = read_var_string(MAX, reader)?;
+        Ok((BoundedString::new(result)?, read))
+    }
+
+    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+        BoundedString::new(read_var_string_json(MAX, jval)?)
+    }
+}
+
 impl<T> XDRIn for Option<T>
 where
     T: XDRIn,
 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let opted = u32::read_xdr(buffer)?.0;
-        let mut read: u64 = 4;
-        if opted == 0 {
-            Ok((None, read))
-        } else {
-            let value_read = T::read_xdr(&buffer[read as usize..])?;
-            read += value_read.1;
-            let value = Some(value_read.0);
-            Ok((value, read))
-        }
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        read_optional(reader, T::read_xdr_from)
     }
 
     fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
-        match jval {
-            JsonValue::Array(vals) if vals.len() <= 1 => match vals.into_iter().next() {
-                Some(val) => {
-                    let value = T::read_json(val)?;
-                    Ok(Some(value))
-                }
-                None => Ok(None),
-            },
-            _ => Err(Error::invalid_json()),
-        }
+        read_optional_json(jval, T::read_json)
+    }
+}
+
+fn read_optional<T, R, F>(reader: &mut XDRReader<R>, read: F) -> Result<(Option<T>, u64), Error>
+where
+    R: Read,
+    F: FnOnce(&mut XDRReader<R>) -> Result<(T, u64), Error>,
+{
+    let opted = u32::read_xdr_from(reader)?.0;
+    if opted == 0 {
+        Ok((None, 4))
+    } else {
+        let (value, read) = reader.nested(read)?;
+        Ok((Some(value), read + 4))
+    }
+}
+
+fn read_optional_json<T, F>(jval: json::JsonValue, read: F) -> Result<Option<T>, Error>
+where
+    F: FnOnce(json::JsonValue) -> Result<T, Error>,
+{
+    match jval {
+        JsonValue::Array(vals) if vals.len() <= 1 => match vals.into_iter().next() {
+            Some(val) => Ok(Some(read(val)?)),
+            None => Ok(None),
+        },
+        _ => Err(Error::invalid_json()),
     }
 }
 
@@ -282,8 +609,9 @@ impl<T> XDRIn for Rc<T>
 where
     T: XDRIn,
 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let (value, read) = T::read_xdr(&buffer)?;
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        reader.reserve_bytes(std::mem::size_of::<T>() as u64)?;
+        let (value, read) = reader.nested(T::read_xdr_from)?;
         Ok((Rc::new(value), read))
     }
 
@@ -297,8 +625,9 @@ impl<T> XDRIn for Arc<T>
 where
     T: XDRIn,
 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let (value, read) = T::read_xdr(&buffer)?;
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        reader.reserve_bytes(std::mem::size_of::<T>() as u64)?;
+        let (value, read) = reader.nested(T::read_xdr_from)?;
         Ok((Arc::new(value), read))
     }
 
@@ -312,8 +641,9 @@ impl<T> XDRIn for Box<T>
 where
     T: XDRIn,
 {
-    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-        let (value, read) = T::read_xdr(&buffer)?;
+    fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(Self, u64), Error> {
+        reader.reserve_bytes(std::mem::size_of::<T>() as u64)?;
+        let (value, read) = reader.nested(T::read_xdr_from)?;
         Ok((Box::new(value), read))
     }
 
@@ -323,6 +653,131 @@ where
     }
 }
 
+/// Decodes a field marked `#[array(fixed = N)]`, which holds exactly `size`
+/// elements or bytes.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(fixed = N)]` cannot be used with `{Self}`",
+    label = "fixed arrays must be a `Vec`, or an `Option` of one"
+)]
+pub trait XDRFixedIn: Sized {
+    fn read_fixed_xdr_from<R: Read>(
+        size: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error>;
+    fn read_fixed_json(size: u32, jval: json::JsonValue) -> Result<Self, Error>;
+}
+
+/// Decodes a field marked `#[array(var = N)]`, which holds at most `max`
+/// elements or bytes.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(var = N)]` cannot be used with `{Self}`",
+    label = "var arrays must be a `Vec` or `String`, or an `Option` of one"
+)]
+pub trait XDRVarIn: Sized {
+    fn read_var_xdr_from<R: Read>(
+        max: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error>;
+    fn read_var_json(max: u32, jval: json::JsonValue) -> Result<Self, Error>;
+}
+
+impl<T> XDRFixedIn for Vec<T>
+where
+    T: XDRIn,
+{
+    fn read_fixed_xdr_from<R: Read>(
+        size: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_fixed_array(size, reader)
+    }
+    fn read_fixed_json(size: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_fixed_array_json(size, jval)
+    }
+}
+
+impl XDRFixedIn for Vec<u8> {
+    fn read_fixed_xdr_from<R: Read>(
+        size: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_fixed_opaque(size, reader)
+    }
+    fn read_fixed_json(size: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_fixed_opaque_json(size, jval)
+    }
+}
+
+impl<T> XDRVarIn for Vec<T>
+where
+    T: XDRIn,
+{
+    fn read_var_xdr_from<R: Read>(
+        max: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_var_array(max, reader)
+    }
+    fn read_var_json(max: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_var_array_json(max, jval)
+    }
+}
+
+impl XDRVarIn for Vec<u8> {
+    fn read_var_xdr_from<R: Read>(
+        max: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_var_opaque(max, reader)
+    }
+    fn read_var_json(max: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_var_opaque_json(max, jval)
+    }
+}
+
+// An optional array is bounded when it is present.
+impl<T> XDRFixedIn for Option<T>
+where
+    T: XDRFixedIn,
+{
+    fn read_fixed_xdr_from<R: Read>(
+        size: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_optional(reader, |reader| T::read_fixed_xdr_from(size, reader))
+    }
+    fn read_fixed_json(size: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_optional_json(jval, |val| T::read_fixed_json(size, val))
+    }
+}
+
+impl<T> XDRVarIn for Option<T>
+where
+    T: XDRVarIn,
+{
+    fn read_var_xdr_from<R: Read>(
+        max: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_optional(reader, |reader| T::read_var_xdr_from(max, reader))
+    }
+    fn read_var_json(max: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_optional_json(jval, |val| T::read_var_json(max, val))
+    }
+}
+
+impl XDRVarIn for String {
+    fn read_var_xdr_from<R: Read>(
+        max: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Self, u64), Error> {
+        read_var_string(max, reader)
+    }
+    fn read_var_json(max: u32, jval: json::JsonValue) -> Result<Self, Error> {
+        read_var_string_json(max, jval)
+    }
+}
+
 pub fn read_fixed_array_json<T: XDRIn>(size: u32, jval: json::JsonValue) -> Result<Vec<T>, Error> {
     let result = Vec::read_json(jval)?;
     if result.len() as u32 != size {
@@ -331,15 +786,22 @@ pub fn read_fixed_array_json<T: XDRIn>(size: u32, jval: json::JsonValue) -> Resu
     Ok(result)
 }
 
-pub fn read_fixed_array<T: XDRIn>(size: u32, buffer: &[u8]) -> Result<(Vec<T>, u64), Error> {
-    let mut read: u64 = 0;
-    let mut result = Vec::new();
-    for _ in 0..size {
-        let t_res = T::read_xdr(&buffer[read as usize..])?;
-        read += t_res.1;
-        result.push(t_res.0);
-    }
-    Ok((result, read))
+pub fn read_fixed_array<T: XDRIn, R: Read>(
+    size: u32,
+    reader: &mut XDRReader<R>,
+) -> Result<(Vec<T>, u64), Error> {
+    reader.reserve_elements(size as u64)?;
+    reader.reserve_bytes(size as u64 * std::mem::size_of::<T>() as u64)?;
+    reader.nested(|reader| {
+        let mut read: u64 = 0;
+        let mut result = Vec::new();
+        for index in 0..size as usize {
+            let t_res = reader.element(index, T::read_xdr_from)?;
+            read += t_res.1;
+            result.push(t_res.0);
+        }
+        Ok((result, read))
+    })
 }
 
 pub fn read_var_array_json<T: XDRIn>(
@@ -353,12 +815,15 @@ pub fn read_var_array_json<T: XDRIn>(
     Ok(result)
 }
 
-pub fn read_var_array<T: XDRIn>(size: u32, buffer: &[u8]) -> Result<(Vec<T>, u64), Error> {
-    let length = u32::read_xdr(buffer)?.0;
+pub fn read_var_array<T: XDRIn, R: Read>(
+    size: u32,
+    reader: &mut XDRReader<R>,
+) -> Result<(Vec<T>, u64), Error> {
+    let length = u32::read_xdr_from(reader)?.0;
     if length > size {
-        return Err(Error::bad_array_size());
+        return Err(Error::bad_array_size().at_offset(reader.position() - 4));
     }
-    let result = read_fixed_array(length, &buffer[4..])?;
+    let result = read_fixed_array(length, reader)?;
     Ok((result.0, result.1 + 4))
 }
 
@@ -370,12 +835,15 @@ pub fn read_var_opaque_json(max_size: u32, jval: json::JsonValue) -> Result<Vec<
     Ok(result)
 }
 
-pub fn read_var_opaque(max_size: u32, buffer: &[u8]) -> Result<(Vec<u8>, u64), Error> {
-    let length = u32::read_xdr(buffer)?.0;
+pub fn read_var_opaque<R: Read>(
+    max_size: u32,
+    reader: &mut XDRReader<R>,
+) -> Result<(Vec<u8>, u64), Error> {
+    let length = u32::read_xdr_from(reader)?.0;
     if length > max_size {
-        return Err(Error::bad_array_size());
+        return Err(Error::bad_array_size().at_offset(reader.position() - 4));
     }
-    let result = read_fixed_opaque(length, &buffer[4..])?;
+    let result = read_fixed_opaque(length, reader)?;
     Ok((result.0, result.1 + 4))
 }
 
@@ -383,6 +851,7 @@ pub fn read_fixed_opaque_json(size: u32, jval: json::JsonValue) -> Result<Vec<u8
     if size <= 64 {
         if jval.is_string() {
             match hex::decode(jval.to_string().as_bytes()) {
+                Ok(val) if val.len() as u32 != size => return Err(Error::bad_array_size()),
                 Ok(val) => return Ok(val),
                 _ => return Err(Error::invalid_json()),
             };
@@ -397,12 +866,13 @@ pub fn read_fixed_opaque_json(size: u32, jval: json::JsonValue) -> Result<Vec<u8
     }
 }
 
-pub fn read_fixed_opaque(size: u32, buffer: &[u8]) -> Result<(Vec<u8>, u64), Error> {
-    let padded_size = (4 - size % 4) % 4 + size;
-    if buffer.len() < padded_size as usize {
-        return Err(Error::bad_array_size());
-    }
-    Ok((buffer[..size as usize].to_vec(), padded_size as u64))
+pub fn read_fixed_opaque<R: Read>(
+    size: u32,
+    reader: &mut XDRReader<R>,
+) -> Result<(Vec<u8>, u64), Error> {
+    let result = read_opaque_body(reader, size)?;
+    let padding = read_padding(reader, size as u64)?;
+    Ok((result, size as u64 + padding))
 }
 
 pub fn read_var_string_json(max_size: u32, jval: json::JsonValue) -> Result<String, Error> {
@@ -413,42 +883,242 @@ pub fn read_var_string_json(max_size: u32, jval: json::JsonValue) -> Result<Stri
     Ok(result)
 }
 
-pub fn read_var_string(max_size: u32, buffer: &[u8]) -> Result<(String, u64), Error> {
-    let length = u32::read_xdr(buffer)?.0;
+pub fn read_var_string<R: Read>(
+    max_size: u32,
+    reader: &mut XDRReader<R>,
+) -> Result<(String, u64), Error> {
+    let length = u32::read_xdr_from(reader)?.0;
     if length > max_size {
-        return Err(Error::var_array_wrong_size());
+        return Err(Error::var_array_wrong_size().at_offset(reader.position() - 4));
     }
-    String::read_xdr(buffer)
+    let (result, read) = read_string_body(reader, length)?;
+    Ok((result, read + 4))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_bool_true() {
-        let to_des: Vec<u8> = vec![0, 0, 0, 1];
-        assert_eq!((true, 4), bool::read_xdr(&to_des).unwrap());
+/// Decoding that borrows opaque data and strings from the input buffer
+/// instead of copying them, for `&'de [u8]`, `&'de str` and `Cow` fields.
+///
+/// Every `XDRIn` type is also `XDRInBorrowed`, and decodes as it normally
+/// would. There is no JSON counterpart, as JSON input is never borrowed.
+pub trait XDRInBorrowed<'de>: Sized {
+    fn read_xdr_borrowed(buffer: &'de [u8]) -> Result<(Self, u64), Error> {
+        Self::read_xdr_borrowed_from(&mut XDRReader::new(buffer))
+    }
+    /// Decodes `buffer` in strict mode, like `XDRIn::read_xdr_strict`.
+    fn read_xdr_borrowed_strict(buffer: &'de [u8]) -> Result<(Self, u64), Error> {
+        let mut reader = XDRReader::new(buffer).strict();
+        let result = Self::read_xdr_borrowed_from(&mut reader)?;
+        reader.expect_end()?;
+        Ok(result)
     }
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error>;
+}
 
-    #[test]
-    fn test_bool_true_json() {
-        let to_des = "true".to_string();
-        let result: bool = read_json_string(to_des).unwrap();
-        assert_eq!(true, result);
+/// The borrowing counterpart of `XDRFixedIn`.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(fixed = N)]` cannot be used with `{Self}`",
+    label = "fixed arrays must be a `Vec` or `&[u8]`, or an `Option` of a `Vec`"
+)]
+pub trait XDRFixedInBorrowed<'de>: Sized {
+    fn read_fixed_xdr_borrowed_from(
+        size: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error>;
+}
+
+/// The borrowing counterpart of `XDRVarIn`.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(var = N)]` cannot be used with `{Self}`",
+    label = "var arrays must be a `Vec`, `String`, `&[u8]` or `&str`, or an `Option` of a `Vec` or `String`"
+)]
+pub trait XDRVarInBorrowed<'de>: Sized {
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error>;
+}
+
+impl<'de, T> XDRInBorrowed<'de> for T
+where
+    T: XDRIn,
+{
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error> {
+        T::read_xdr_from(reader)
     }
+}
 
-    #[test]
-    fn test_bool_false() {
-        let to_des: Vec<u8> = vec![0, 0, 0, 0];
-        assert_eq!((false, 4), bool::read_xdr(&to_des).unwrap());
+impl<'de, T> XDRFixedInBorrowed<'de> for T
+where
+    T: XDRFixedIn,
+{
+    fn read_fixed_xdr_borrowed_from(
+        size: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        T::read_fixed_xdr_from(size, reader)
     }
+}
 
-    #[test]
-    fn test_bool_false_json() {
-        let to_des = "false".to_string();
-        let result: bool = read_json_string(to_des).unwrap();
-        assert_eq!(false, result);
+impl<'de, T> XDRVarInBorrowed<'de> for T
+where
+    T: XDRVarIn,
+{
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        T::read_var_xdr_from(max, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRInBorrowed<'de> for &'a [u8] {
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error> {
+        read_var_opaque_borrowed(u32::MAX, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRFixedInBorrowed<'de> for &'a [u8] {
+    fn read_fixed_xdr_borrowed_from(
+        size: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        read_fixed_opaque_borrowed(size, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRVarInBorrowed<'de> for &'a [u8] {
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        read_var_opaque_borrowed(max, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRInBorrowed<'de> for &'a str {
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error> {
+        read_var_str_borrowed(u32::MAX, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRVarInBorrowed<'de> for &'a str {
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        read_var_str_borrowed(max, reader)
+    }
+}
+
+impl<'de: 'a, 'a> XDRInBorrowed<'de> for Cow<'a, [u8]> {
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error> {
+        let (bytes, read) = read_var_opaque_borrowed(u32::MAX, reader)?;
+        Ok((Cow::Borrowed(bytes), read))
+    }
+}
+
+impl<'de: 'a, 'a> XDRFixedInBorrowed<'de> for Cow<'a, [u8]> {
+    fn read_fixed_xdr_borrowed_from(
+        size: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        let (bytes, read) = read_fixed_opaque_borrowed(size, reader)?;
+        Ok((Cow::Borrowed(bytes), read))
+    }
+}
+
+impl<'de: 'a, 'a> XDRVarInBorrowed<'de> for Cow<'a, [u8]> {
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        let (bytes, read) = read_var_opaque_borrowed(max, reader)?;
+        Ok((Cow::Borrowed(bytes), read))
+    }
+}
+
+impl<'de: 'a, 'a> XDRInBorrowed<'de> for Cow<'a, str> {
+    fn read_xdr_borrowed_from(reader: &mut XDRReader<&'de [u8]>) -> Result<(Self, u64), Error> {
+        let (val, read) = read_var_str_borrowed(u32::MAX, reader)?;
+        Ok((Cow::Borrowed(val), read))
+    }
+}
+
+impl<'de: 'a, 'a> XDRVarInBorrowed<'de> for Cow<'a, str> {
+    fn read_var_xdr_borrowed_from(
+        max: u32,
+        reader: &mut XDRReader<&'de [u8]>,
+    ) -> Result<(Self, u64), Error> {
+        let (val, read) = read_var_str_borrowed(max, reader)?;
+        Ok((Cow::Borrowed(val), read))
+    }
+}
+
+pub fn read_fixed_opaque_borrowed<'de>(
+    size: u32,
+    reader: &mut XDRReader<&'de [u8]>,
+) -> Result<(&'de [u8], u64), Error> {
+    let result = reader.borrow_bytes(size)?;
+    let padding = read_padding(reader, size as u64)?;
+    Ok((result, size as u64 + padding))
+}
+
+pub fn read_var_opaque_borrowed<'de>(
+    max_size: u32,
+    reader: &mut XDRReader<&'de [u8]>,
+) -> Result<(&'de [u8], u64), Error> {
+    let length = u32::read_xdr_from(reader)?.0;
+    if length > max_size {
+        return Err(Error::bad_array_size().at_offset(reader.position() - 4));
+    }
+    let result = read_fixed_opaque_borrowed(length, reader)?;
+    Ok((result.0, result.1 + 4))
+}
+
+pub fn read_var_str_borrowed<'de>(
+    max_size: u32,
+    reader: &mut XDRReader<&'de [u8]>,
+) -> Result<(&'de str, u64), Error> {
+    let length = u32::read_xdr_from(reader)?.0;
+    if length > max_size {
+        return Err(Error::var_array_wrong_size().at_offset(reader.position() - 4));
+    }
+    let start = reader.position();
+    let bytes = reader.borrow_bytes(length)?;
+    let result = std::str::from_utf8(bytes).map_err(|e| Error::from(e).at_offset(start))?;
+    let read = length as u64 + read_padding(reader, length as u64)?;
+    Ok((result, read + 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn test_bool_true() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1];
+        assert_eq!((true, 4), bool::read_xdr(&to_des).unwrap());
+    }
+
+    #[test]
+    fn test_bool_true_json() {
+        let to_des = "true".to_string();
+        let result: bool = read_json_string(to_des).unwrap();
+        assert!(result);
+    }
+
+    #[test]
+    fn test_bool_false() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 0];
+        assert_eq!((false, 4), bool::read_xdr(&to_des).unwrap());
+    }
+
+    #[test]
+    fn test_bool_false_json() {
+        let to_des = "false".to_string();
+        let result: bool = read_json_string(to_des).unwrap();
+        assert!(!result);
     }
 
     #[test]
@@ -458,7 +1128,7 @@ mod tests {
         let err_3: Vec<u8> = vec![0, 0, 0];
         assert_eq!(Err(Error::bool_bad_format()), bool::read_xdr(&err_1));
         assert_eq!(Err(Error::bool_bad_format()), bool::read_xdr(&err_2));
-        assert_eq!(Err(Error::bool_bad_format()), bool::read_xdr(&err_3));
+        assert_eq!(Err(Error::unexpected_eof()), bool::read_xdr(&err_3));
 
         let to_des = "123".to_string();
         let result: Result<bool, Error> = read_json_string(to_des);
@@ -481,7 +1151,7 @@ mod tests {
     #[test]
     fn test_int_error() {
         let to_des: Vec<u8> = vec![255, 255, 255];
-        assert_eq!(Err(Error::integer_bad_format()), i32::read_xdr(&to_des));
+        assert_eq!(Err(Error::unexpected_eof()), i32::read_xdr(&to_des));
 
         let to_des = "true".to_string();
         let result: Result<i32, Error> = read_json_string(to_des);
@@ -491,7 +1161,7 @@ mod tests {
     #[test]
     fn test_uint() {
         let to_des: Vec<u8> = vec![255, 255, 255, 255];
-        assert_eq!((std::u32::MAX, 4), u32::read_xdr(&to_des).unwrap());
+        assert_eq!((u32::MAX, 4), u32::read_xdr(&to_des).unwrap());
     }
 
     #[test]
@@ -504,10 +1174,7 @@ mod tests {
     #[test]
     fn test_uint_error() {
         let to_des: Vec<u8> = vec![255, 255, 255];
-        assert_eq!(
-            Err(Error::unsigned_integer_bad_format()),
-            u32::read_xdr(&to_des)
-        );
+        assert_eq!(Err(Error::unexpected_eof()), u32::read_xdr(&to_des));
 
         let to_des = "true".to_string();
         let result: Result<u32, Error> = read_json_string(to_des);
@@ -530,7 +1197,7 @@ mod tests {
     #[test]
     fn test_hyper_error() {
         let to_des: Vec<u8> = vec![255, 255, 255, 255, 255, 255, 255];
-        assert_eq!(Err(Error::hyper_bad_format()), i64::read_xdr(&to_des));
+        assert_eq!(Err(Error::unexpected_eof()), i64::read_xdr(&to_des));
 
         let to_des = "123".to_string();
         let result: Result<i64, Error> = read_json_string(to_des);
@@ -540,7 +1207,7 @@ mod tests {
     #[test]
     fn test_uhyper() {
         let to_des: Vec<u8> = vec![255, 255, 255, 255, 255, 255, 255, 255];
-        assert_eq!((std::u64::MAX, 8), u64::read_xdr(&to_des).unwrap());
+        assert_eq!((u64::MAX, 8), u64::read_xdr(&to_des).unwrap());
     }
 
     #[test]
@@ -553,10 +1220,7 @@ mod tests {
     #[test]
     fn test_uhyper_error() {
         let to_des: Vec<u8> = vec![255, 255, 255, 255, 255, 255, 255];
-        assert_eq!(
-            Err(Error::unsigned_hyper_bad_format()),
-            u64::read_xdr(&to_des)
-        );
+        assert_eq!(Err(Error::unexpected_eof()), u64::read_xdr(&to_des));
 
         let to_des = "123".to_string();
         let result: Result<u64, Error> = read_json_string(to_des);
@@ -579,7 +1243,7 @@ mod tests {
     #[test]
     fn test_float_error() {
         let to_des: Vec<u8> = vec![255, 255, 255];
-        assert_eq!(Err(Error::float_bad_format()), f32::read_xdr(&to_des));
+        assert_eq!(Err(Error::unexpected_eof()), f32::read_xdr(&to_des));
 
         let to_des = "true".to_string();
         let result: Result<f32, Error> = read_json_string(to_des);
@@ -602,13 +1266,52 @@ mod tests {
     #[test]
     fn test_double_error() {
         let to_des: Vec<u8> = vec![255, 255, 255, 255, 255, 255, 255];
-        assert_eq!(Err(Error::double_bad_format()), f64::read_xdr(&to_des));
+        assert_eq!(Err(Error::unexpected_eof()), f64::read_xdr(&to_des));
 
         let to_des = "true".to_string();
         let result: Result<f64, Error> = read_json_string(to_des);
         assert_eq!(Err(Error::double_bad_format()), result);
     }
 
+    #[test]
+    fn test_quad() {
+        let to_des: Vec<u8> = vec![0x3f, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        assert_eq!(
+            (
+                Quad::from_bits(0x3fff_0000_0000_0000_0000_0000_0000_0001),
+                16
+            ),
+            Quad::read_xdr(&to_des).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quad_json() {
+        let result: Quad = read_json_string("-2.5".to_string()).unwrap();
+        assert_eq!(Quad::from(-2.5), result);
+
+        let to_des = r#""3fff0000000000000000000000000001""#.to_string();
+        let result: Quad = read_json_string(to_des).unwrap();
+        assert_eq!(
+            Quad::from_bits(0x3fff_0000_0000_0000_0000_0000_0000_0001),
+            result
+        );
+    }
+
+    #[test]
+    fn test_quad_error() {
+        let to_des: Vec<u8> = vec![0x3f, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(Err(Error::unexpected_eof()), Quad::read_xdr(&to_des));
+
+        let to_des = r#""3fff""#.to_string();
+        let result: Result<Quad, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::quad_bad_format()), result);
+
+        let to_des = "true".to_string();
+        let result: Result<Quad, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::quad_bad_format()), result);
+    }
+
     #[test]
     fn test_var_opaque_no_padding() {
         let to_des: Vec<u8> = vec![0, 0, 0, 8, 3, 3, 3, 4, 1, 2, 3, 4];
@@ -655,7 +1358,7 @@ mod tests {
     fn test_var_array_error() {
         let to_des: Vec<u8> = vec![0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0];
         let result: Result<(Vec<u32>, u64), Error> = Vec::read_xdr(&to_des);
-        assert_eq!(Err(Error::unsigned_integer_bad_format()), result);
+        assert_eq!(Err(Error::unexpected_eof()), result);
 
         let to_des = "[false]".to_string();
         let result: Result<Vec<u32>, Error> = read_json_string(to_des);
@@ -688,7 +1391,7 @@ mod tests {
     fn test_struct_error() {
         let to_des: Vec<u8> = vec![0x3f, 0x80, 0, 0, 0, 0, 0];
         let result: Result<(TestStruct, u64), Error> = TestStruct::read_xdr(&to_des);
-        assert_eq!(Err(Error::unsigned_integer_bad_format()), result);
+        assert_eq!(Err(Error::unexpected_eof()), result);
 
         let to_des = r#"{"asdf": 1.0, "two": 34}"#.to_string();
         let result: Result<TestStruct, Error> = read_json_string(to_des);
@@ -839,11 +1542,19 @@ mod tests {
     fn test_fixed_opaque_no_padding_error() {
         let to_des: Vec<u8> = vec![3, 3, 3, 4, 1, 2, 3];
         let result = TestFixedOpaqueNoPadding::read_xdr(&to_des);
-        assert_eq!(Err(Error::bad_array_size()), result);
+        assert_eq!(Err(Error::unexpected_eof()), result);
 
         let to_des = r#"{"opaque": "t000000000000000"}"#.to_string();
         let result: Result<TestFixedOpaqueNoPadding, Error> = read_json_string(to_des);
         assert_eq!(Err(Error::invalid_json()), result);
+
+        let to_des = r#"{"opaque": "00000000000000"}"#.to_string();
+        let result: Result<TestFixedOpaqueNoPadding, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::bad_array_size()), result);
+
+        let to_des = r#"{"opaque": "000000000000000000"}"#.to_string();
+        let result: Result<TestFixedOpaqueNoPadding, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::bad_array_size()), result);
     }
 
     #[derive(XDRIn, Debug, PartialEq)]
@@ -866,7 +1577,7 @@ mod tests {
     fn test_fixed_opaque_padding_error() {
         let to_des: Vec<u8> = vec![3, 3, 3, 4, 1, 0, 0];
         let result = TestFixedOpaquePadding::read_xdr(&to_des);
-        assert_eq!(Err(Error::bad_array_size()), result);
+        assert_eq!(Err(Error::unexpected_eof()), result);
     }
 
     #[derive(XDRIn, Debug, PartialEq)]
@@ -929,7 +1640,7 @@ mod tests {
     fn test_fixed_array_error() {
         let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0];
         let result = TestFixedArray::read_xdr(&to_des);
-        assert_eq!(Err(Error::unsigned_integer_bad_format()), result);
+        assert_eq!(Err(Error::unexpected_eof()), result);
 
         let to_des = r#"{"data": [1, 2]}"#.to_string();
         let result: Result<TestFixedArray, Error> = read_json_string(to_des);
@@ -937,6 +1648,7 @@ mod tests {
     }
 
     #[derive(XDRIn, Debug, PartialEq)]
+    #[xdr(transparent)]
     struct TestFixedArrayType {
         #[array(fixed = 3)]
         pub t: Vec<u32>,
@@ -1082,6 +1794,160 @@ mod tests {
         assert_eq!(TestUnion::Third(()), result);
     }
 
+    #[derive(XDRIn, Debug, PartialEq)]
+    enum TestNegativeEnum {
+        Minus = -1,
+        Plus = 1,
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    enum TestUnionGeneric {
+        List(Vec<u32>),
+        Maybe(Option<Box<TestNegativeEnum>>),
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestPage<T>
+    where
+        T: Clone,
+    {
+        items: Vec<T>,
+        next: Option<Box<TestPage<T>>>,
+    }
+
+    #[derive(XDRIn, Clone, Debug, PartialEq)]
+    enum TestGenericUnion<T> {
+        #[discriminant(value = "1")]
+        Value(T),
+        #[discriminant(value = "2")]
+        Nothing(()),
+    }
+
+    #[test]
+    fn test_generics() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 5, 0, 0, 0, 0];
+        assert_eq!(
+            Ok((
+                TestPage {
+                    items: vec![TestGenericUnion::Value(5u32)],
+                    next: None,
+                },
+                16
+            )),
+            TestPage::read_xdr(&to_des)
+        );
+
+        let result: TestPage<i64> = read_json_string(
+            r#"{"items": ["-3"], "next": [{"items": [], "next": []}]}"#.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            TestPage {
+                items: vec![-3],
+                next: Some(Box::new(TestPage {
+                    items: vec![],
+                    next: None,
+                })),
+            },
+            result
+        );
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestNewtype(#[array(fixed = 4)] Vec<u8>);
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestTuple(u32, TestNewtype, String);
+
+    #[test]
+    fn test_tuple_structs() {
+        assert_eq!(
+            Ok((TestNewtype(vec![1, 2, 3, 4]), 4)),
+            TestNewtype::read_xdr(&[1, 2, 3, 4])
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0, 0, 255, 0, 0, 0, 1, 97, 0, 0, 0];
+        let expected = TestTuple(7, TestNewtype(vec![0, 0, 0, 255]), "a".to_string());
+        assert_eq!(Ok((expected, 16)), TestTuple::read_xdr(&to_des));
+
+        let result: TestTuple = read_json_string(r#"[7, "000000ff", "a"]"#.to_string()).unwrap();
+        assert_eq!(
+            TestTuple(7, TestNewtype(vec![0, 0, 0, 255]), "a".to_string()),
+            result
+        );
+
+        let err = TestTuple::read_xdr(&to_des[..6]).unwrap_err();
+        assert_eq!("1", err.path());
+        let err = read_json_string::<TestTuple>(r#"[7, "000000ff"]"#.to_string()).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidJson, err.kind());
+        assert_eq!("2", err.path());
+    }
+
+    // Nothing from the library is in scope here, and the local names shadow
+    // the ones the derives use.
+    #[allow(dead_code)]
+    mod hygiene {
+        pub struct Error;
+        pub type Result<T> = std::result::Result<T, Error>;
+        pub trait Write {}
+        pub mod json {}
+
+        #[derive(XDROut, XDRIn, Debug, PartialEq)]
+        pub struct Wrapper {
+            pub inner: Option<Box<Inner>>,
+            #[array(var = 4)]
+            pub data: Vec<u8>,
+        }
+
+        #[derive(XDROut, XDRIn, Debug, PartialEq)]
+        #[xdr(crate = "crate")]
+        pub enum Inner {
+            A = 0,
+        }
+    }
+
+    #[test]
+    fn test_hygiene() {
+        let value = hygiene::Wrapper {
+            inner: Some(Box::new(hygiene::Inner::A)),
+            data: vec![1],
+        };
+        let bytes = crate::ser::to_xdr_bytes(&value).unwrap();
+        assert_eq!(Ok(value), from_xdr_exact(&bytes));
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestKeyword {
+        r#type: u32,
+    }
+
+    #[test]
+    fn test_generated_shapes() {
+        let to_des: Vec<u8> = vec![255, 255, 255, 255];
+        assert_eq!(
+            Ok((TestNegativeEnum::Minus, 4)),
+            TestNegativeEnum::read_xdr(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 7];
+        assert_eq!(
+            Ok((TestUnionGeneric::List(vec![7]), 12)),
+            TestUnionGeneric::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1];
+        assert_eq!(
+            Ok((
+                TestUnionGeneric::Maybe(Some(Box::new(TestNegativeEnum::Plus))),
+                12
+            )),
+            TestUnionGeneric::read_xdr(&to_des)
+        );
+
+        let result: TestKeyword = read_json_string(r#"{"type": 3}"#.to_string()).unwrap();
+        assert_eq!(TestKeyword { r#type: 3 }, result);
+        let err = TestKeyword::read_xdr(&[0, 0]).unwrap_err();
+        assert_eq!("type", err.path());
+    }
+
     #[test]
     fn test_union_error() {
         let to_des_1: Vec<u8> = vec![0, 0, 0, 3, 0x3f, 0x80, 0, 0, 0, 0, 0, 2];
@@ -1091,10 +1957,7 @@ mod tests {
         );
 
         let to_des_2: Vec<u8> = vec![0, 0, 0, 0, 0x3f, 0x80];
-        assert_eq!(
-            Err(Error::unsigned_integer_bad_format()),
-            TestUnion::read_xdr(&to_des_2)
-        );
+        assert_eq!(Err(Error::unexpected_eof()), TestUnion::read_xdr(&to_des_2));
 
         let to_des = r#"{"type":0,"data": "asdf"}"#.to_string();
         let result: Result<TestUnion, Error> = read_json_string(to_des);
@@ -1142,47 +2005,1159 @@ mod tests {
         assert_eq!(TestUnionDiscriminant::Third(()), result);
     }
 
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestUnionDefault {
+        #[discriminant(value = "1")]
+        Known(u32),
+        #[discriminant(default)]
+        Unknown(i32, u32),
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    enum TestUnionVoidDefault {
+        #[discriminant(value = "1")]
+        Known(u32),
+        #[discriminant(default)]
+        Unknown(i32),
+    }
+
     #[test]
-    fn test_union_discriminant_error() {
-        let to_des_1: Vec<u8> = vec![0, 0, 0, 0, 0x3f, 0x80, 0, 0, 0, 0, 0, 2];
+    fn test_union_default() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 3];
         assert_eq!(
-            Err(Error::invalid_enum_value()),
-            TestUnionDiscriminant::read_xdr(&to_des_1)
+            Ok((TestUnionDefault::Known(3), 8)),
+            TestUnionDefault::read_xdr(&to_des)
         );
 
-        let to_des_2: Vec<u8> = vec![255, 255, 255, 255, 0x3f, 0x80];
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0, 0, 3];
+        let result = TestUnionDefault::read_xdr(&to_des);
+        assert_eq!(Ok((TestUnionDefault::Unknown(7, 3), 8)), result);
+        let forwarded = crate::ser::to_xdr_bytes(&result.unwrap().0).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        let to_des: Vec<u8> = vec![255, 255, 255, 254];
         assert_eq!(
-            Err(Error::unsigned_integer_bad_format()),
-            TestUnionDiscriminant::read_xdr(&to_des_2)
+            Ok((TestUnionVoidDefault::Unknown(-2), 4)),
+            TestUnionVoidDefault::read_xdr(&to_des)
         );
 
-        let to_des = r#"{"type":-1,"data": "asdf"}"#.to_string();
-        let result: Result<TestUnionDiscriminant, Error> = read_json_string(to_des);
-        assert_eq!(Err(Error::unsigned_integer_bad_format()), result);
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0];
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            TestUnionDefault::read_xdr(&to_des)
+        );
+    }
+
+    #[test]
+    fn test_union_default_json() {
+        let to_des = r#"{"type":7,"data":3}"#.to_string();
+        let result: TestUnionDefault = read_json_string(to_des).unwrap();
+        assert_eq!(TestUnionDefault::Unknown(7, 3), result);
+
+        let to_des = r#"{"type":-2,"data":""}"#.to_string();
+        let result: TestUnionVoidDefault = read_json_string(to_des).unwrap();
+        assert_eq!(TestUnionVoidDefault::Unknown(-2), result);
+
+        let to_des = r#"{"type":-2,"data":3}"#.to_string();
+        let result: Result<TestUnionVoidDefault, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::invalid_json()), result);
     }
 
     #[derive(XDRIn, Debug, PartialEq)]
-    pub struct ID {
-        #[array(fixed = 32)]
-        pub t: Vec<u8>,
+    enum TestKind {
+        Payment = 0,
+        Create = 1,
+        Merge = 2,
     }
 
     #[derive(XDRIn, Debug, PartialEq)]
-    pub struct User {
-        pub id: ID,
+    enum TestEnumUnion {
+        #[discriminant(enum = "TestKind::Payment")]
+        Payment(u32),
+        #[discriminant(enum = "TestKind::Create")]
+        Create(()),
+        #[discriminant(default)]
+        Other(TestKind),
+    }
 
-        #[array(var = 80)]
-        pub name: String,
+    #[derive(XDRIn, Debug, PartialEq)]
+    enum TestUnsignedUnion {
+        #[discriminant(unsigned = "4294967295")]
+        Max(u32),
+        #[discriminant(unsigned = "1")]
+        One(()),
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    enum TestBoolUnion {
+        #[discriminant(bool = "true")]
+        Yes(u32),
+        #[discriminant(bool = "false")]
+        No(()),
     }
 
     #[test]
-    fn test_array_complex() {
-        let to_des = r#"[{"id":"0000000000000000000000000000000000000000000000000000000000000000","name":"sam"}]"#.to_string();
-        let result: Vec<User> = read_json_string(to_des).unwrap();
-        let expected: Vec<User> = vec![User {
-            id: ID { t: vec![0; 32] },
-            name: "sam".to_string(),
-        }];
-        assert_eq!(expected, result);
+    fn test_union_typed_discriminant() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 5];
+        assert_eq!(
+            Ok((TestEnumUnion::Payment(5), 8)),
+            TestEnumUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 1];
+        assert_eq!(
+            Ok((TestEnumUnion::Create(()), 4)),
+            TestEnumUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 2];
+        assert_eq!(
+            Ok((TestEnumUnion::Other(TestKind::Merge), 4)),
+            TestEnumUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 9];
+        assert_eq!(
+            Err(Error::invalid_enum_value()),
+            TestEnumUnion::read_xdr(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![255, 255, 255, 255, 0, 0, 0, 3];
+        assert_eq!(
+            Ok((TestUnsignedUnion::Max(3), 8)),
+            TestUnsignedUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 2];
+        assert_eq!(
+            Err(Error::invalid_enum_value()),
+            TestUnsignedUnion::read_xdr(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 3];
+        assert_eq!(
+            Ok((TestBoolUnion::Yes(3), 8)),
+            TestBoolUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 0];
+        assert_eq!(
+            Ok((TestBoolUnion::No(()), 4)),
+            TestBoolUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 2];
+        assert_eq!(
+            Err(Error::bool_bad_format()),
+            TestBoolUnion::read_xdr(&to_des)
+        );
+    }
+
+    #[test]
+    fn test_union_typed_discriminant_json() {
+        let to_des = r#"{"type":0,"data":5}"#.to_string();
+        let result: TestEnumUnion = read_json_string(to_des).unwrap();
+        assert_eq!(TestEnumUnion::Payment(5), result);
+
+        let to_des = r#"{"type":2,"data":""}"#.to_string();
+        let result: TestEnumUnion = read_json_string(to_des).unwrap();
+        assert_eq!(TestEnumUnion::Other(TestKind::Merge), result);
+
+        let to_des = r#"{"type":9,"data":""}"#.to_string();
+        let result: Result<TestEnumUnion, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::invalid_enum_value()), result);
+
+        let to_des = r#"{"type":4294967295,"data":3}"#.to_string();
+        let result: TestUnsignedUnion = read_json_string(to_des).unwrap();
+        assert_eq!(TestUnsignedUnion::Max(3), result);
+
+        let to_des = r#"{"type":true,"data":3}"#.to_string();
+        let result: TestBoolUnion = read_json_string(to_des).unwrap();
+        assert_eq!(TestBoolUnion::Yes(3), result);
+
+        let to_des = r#"{"type":1,"data":3}"#.to_string();
+        let result: Result<TestBoolUnion, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::bool_bad_format()), result);
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestUnionFields {
+        #[discriminant(value = "0")]
+        Pay {
+            to: u32,
+            #[array(var = 8)]
+            memo: String,
+        },
+        #[discriminant(value = "1")]
+        Swap(u32, #[array(fixed = 2)] Vec<u8>),
+        #[discriminant(value = "2")]
+        Out { out: u32, reader: u32 },
+    }
+
+    #[test]
+    fn test_union_fields() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 2, 104, 105, 0, 0];
+        let result = TestUnionFields::read_xdr(&to_des);
+        assert_eq!(
+            Ok((
+                TestUnionFields::Pay {
+                    to: 3,
+                    memo: "hi".to_string()
+                },
+                16
+            )),
+            result
+        );
+        let forwarded = crate::ser::to_xdr_bytes(&result.unwrap().0).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 3, 7, 8, 0, 0];
+        assert_eq!(
+            Ok((TestUnionFields::Swap(3, vec![7, 8]), 12)),
+            TestUnionFields::read_xdr(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4];
+        assert_eq!(
+            Ok((TestUnionFields::Out { out: 3, reader: 4 }, 12)),
+            TestUnionFields::read_xdr(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 9];
+        let err = TestUnionFields::read_xdr(&to_des).unwrap_err();
+        assert_eq!(Error::var_array_wrong_size(), err);
+        assert_eq!("memo", err.path());
+    }
+
+    #[test]
+    fn test_union_fields_json() {
+        let to_des = r#"{"type":0,"data":{"to":3,"memo":"hi"}}"#.to_string();
+        let result: TestUnionFields = read_json_string(to_des).unwrap();
+        assert_eq!(
+            TestUnionFields::Pay {
+                to: 3,
+                memo: "hi".to_string()
+            },
+            result
+        );
+
+        let to_des = r#"{"type":1,"data":[3,"0708"]}"#.to_string();
+        let result: TestUnionFields = read_json_string(to_des).unwrap();
+        assert_eq!(TestUnionFields::Swap(3, vec![7, 8]), result);
+
+        let to_des = r#"{"type":0,"data":{"to":3}}"#.to_string();
+        let err = read_json_string::<TestUnionFields>(to_des).unwrap_err();
+        assert_eq!(Error::invalid_json(), err);
+        assert_eq!("memo", err.path());
+    }
+
+    // Encodes a `u16`, which XDR lacks, as an unsigned int.
+    mod as_u32 {
+        use crate::de::{XDRIn, XDRReader};
+        use crate::error::Error;
+        use crate::ser::XDROut;
+        use json::JsonValue;
+        use std::convert::TryFrom;
+        use std::io::{Read, Write};
+
+        pub fn write_xdr<W: Write + ?Sized>(val: &u16, out: &mut W) -> Result<u64, Error> {
+            u32::from(*val).write_xdr(out)
+        }
+
+        pub fn write_json<W: Write + ?Sized>(val: &u16, out: &mut W) -> Result<u64, Error> {
+            u32::from(*val).write_json(out)
+        }
+
+        pub fn xdr_size(val: &u16) -> u64 {
+            u32::from(*val).xdr_size()
+        }
+
+        pub fn read_xdr_from<R: Read>(reader: &mut XDRReader<R>) -> Result<(u16, u64), Error> {
+            let (val, read) = u32::read_xdr_from(reader)?;
+            let val = u16::try_from(val).map_err(|_| Error::unsigned_integer_bad_format())?;
+            Ok((val, read))
+        }
+
+        pub fn read_json(jval: JsonValue) -> Result<u16, Error> {
+            u16::try_from(u32::read_json(jval)?).map_err(|_| Error::unsigned_integer_bad_format())
+        }
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    struct TestFieldAttrs {
+        #[xdr(rename = "type")]
+        kind: u32,
+        #[xdr(skip)]
+        cache: Vec<u32>,
+        #[xdr(with = "as_u32")]
+        port: u16,
+        #[xdr(default)]
+        #[array(var = 8)]
+        memo: String,
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    struct TestSkipTuple(u32, #[xdr(skip)] u32, #[xdr(default)] u32);
+
+    #[test]
+    fn test_field_attrs() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 80, 0, 0, 0, 2, 104, 105, 0, 0];
+        let result = TestFieldAttrs::read_xdr(&to_des);
+        assert_eq!(
+            Ok((
+                TestFieldAttrs {
+                    kind: 1,
+                    cache: Vec::new(),
+                    port: 80,
+                    memo: "hi".to_string(),
+                },
+                16
+            )),
+            result
+        );
+        let mut value = result.unwrap().0;
+        value.cache = vec![1, 2];
+        assert_eq!(to_des, crate::ser::to_xdr_bytes(&value).unwrap());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0];
+        let err = TestFieldAttrs::read_xdr(&to_des).unwrap_err();
+        assert_eq!(Error::unsigned_integer_bad_format(), err);
+        assert_eq!("port", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 3];
+        assert_eq!(
+            Ok((TestSkipTuple(1, 0, 3), 8)),
+            TestSkipTuple::read_xdr(&to_des)
+        );
+    }
+
+    #[test]
+    fn test_field_attrs_json() {
+        let to_des = r#"{"type":1,"port":80}"#.to_string();
+        let result: TestFieldAttrs = read_json_string(to_des).unwrap();
+        assert_eq!(
+            TestFieldAttrs {
+                kind: 1,
+                cache: Vec::new(),
+                port: 80,
+                memo: String::new(),
+            },
+            result
+        );
+
+        let to_des = r#"{"type":1,"port":80,"memo":"too long for it"}"#.to_string();
+        let err = read_json_string::<TestFieldAttrs>(to_des).unwrap_err();
+        assert_eq!(Error::bad_array_size(), err);
+        assert_eq!("memo", err.path());
+
+        let to_des = r#"{"kind":1,"port":80}"#.to_string();
+        let err = read_json_string::<TestFieldAttrs>(to_des).unwrap_err();
+        assert_eq!(Error::invalid_json(), err);
+        assert_eq!("type", err.path());
+
+        let to_des = "[1]".to_string();
+        let result: TestSkipTuple = read_json_string(to_des).unwrap();
+        assert_eq!(TestSkipTuple(1, 0, 0), result);
+        let json = crate::ser::to_json_string(&TestSkipTuple(1, 2, 3)).unwrap();
+        assert_eq!("[1,3]", json);
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    struct TestBounded {
+        hash: [u8; 3],
+        points: [i32; 2],
+        memo: VarOpaque<4>,
+        signers: VarArray<u32, 2>,
+        name: BoundedString<4>,
+    }
+
+    #[test]
+    fn test_bounded_types() {
+        let to_des: Vec<u8> = vec![
+            1, 2, 3, 0, 0, 0, 0, 4, 255, 255, 255, 255, 0, 0, 0, 1, 9, 0, 0, 0, 0, 0, 0, 1, 0, 0,
+            0, 7, 0, 0, 0, 2, 104, 105, 0, 0,
+        ];
+        let result = TestBounded::read_xdr(&to_des);
+        assert_eq!(
+            Ok((
+                TestBounded {
+                    hash: [1, 2, 3],
+                    points: [4, -1],
+                    memo: VarOpaque::new(vec![9]).unwrap(),
+                    signers: VarArray::new(vec![7]).unwrap(),
+                    name: BoundedString::new("hi".to_string()).unwrap(),
+                },
+                36
+            )),
+            result
+        );
+        let forwarded = crate::ser::to_xdr_bytes(&result.unwrap().0).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 5, 1, 2, 3, 4, 5, 0, 0, 0];
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            VarOpaque::<4>::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3];
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            VarArray::<u32, 2>::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 5, 104, 101, 108, 108, 111, 0, 0, 0];
+        assert_eq!(
+            Err(Error::var_array_wrong_size()),
+            BoundedString::<4>::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![1, 2, 3];
+        assert_eq!(Err(Error::unexpected_eof()), <[u8; 3]>::read_xdr(&to_des));
+    }
+
+    #[test]
+    fn test_bounded_types_json() {
+        let to_des = r#"{"hash":"010203","points":[4,-1],"memo":"CQ==","signers":[7],"name":"hi"}"#
+            .to_string();
+        let result: TestBounded = read_json_string(to_des).unwrap();
+        assert_eq!(
+            TestBounded {
+                hash: [1, 2, 3],
+                points: [4, -1],
+                memo: VarOpaque::new(vec![9]).unwrap(),
+                signers: VarArray::new(vec![7]).unwrap(),
+                name: BoundedString::new("hi".to_string()).unwrap(),
+            },
+            result
+        );
+
+        let result: Result<[u8; 3], Error> = read_json_string(r#""0102""#.to_string());
+        assert_eq!(Err(Error::bad_array_size()), result);
+        let result: Result<[i32; 2], Error> = read_json_string("[1,2,3]".to_string());
+        assert_eq!(Err(Error::bad_array_size()), result);
+        let result: Result<VarArray<u32, 2>, Error> = read_json_string("[1,2,3]".to_string());
+        assert_eq!(Err(Error::bad_array_size()), result);
+        let result: Result<BoundedString<4>, Error> = read_json_string(r#""hello""#.to_string());
+        assert_eq!(Err(Error::bad_array_size()), result);
+    }
+
+    // A single-field arm can't carry `#[array]`, so its bound lives in the
+    // arm's type.
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestBoundedArm {
+        #[discriminant(value = "0")]
+        Data(VarOpaque<2>),
+    }
+
+    #[test]
+    fn test_bounded_single_field_arm() {
+        let value = TestBoundedArm::Data(VarOpaque::new(vec![1, 2]).unwrap());
+        let encoded = crate::ser::to_xdr_bytes(&value).unwrap();
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 0, 0], encoded);
+        assert_eq!(Ok((value, 12)), TestBoundedArm::read_xdr(&encoded));
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 3, 1, 2, 3, 0];
+        assert_eq!(
+            &ErrorKind::BadArraySize,
+            TestBoundedArm::read_xdr(&to_des).unwrap_err().kind()
+        );
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestEnumBounds {
+        Min = -2147483648,
+        Max = 2147483647,
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestUnionBounds {
+        #[discriminant(value = "-2147483648")]
+        Min(()),
+        #[discriminant(value = "2147483647")]
+        Max(()),
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestUnsignedBounds {
+        #[discriminant(unsigned = "0")]
+        Min(()),
+        #[discriminant(unsigned = "4294967295")]
+        Max(()),
+    }
+
+    #[test]
+    fn test_discriminant_bounds() {
+        let min: Vec<u8> = vec![128, 0, 0, 0];
+        let max: Vec<u8> = vec![127, 255, 255, 255];
+        assert_eq!(Ok((TestEnumBounds::Min, 4)), TestEnumBounds::read_xdr(&min));
+        assert_eq!(Ok((TestEnumBounds::Max, 4)), TestEnumBounds::read_xdr(&max));
+        assert_eq!(
+            Ok((TestUnionBounds::Min(()), 4)),
+            TestUnionBounds::read_xdr(&min)
+        );
+        assert_eq!(
+            Ok((TestUnionBounds::Max(()), 4)),
+            TestUnionBounds::read_xdr(&max)
+        );
+        assert_eq!(
+            Ok((TestUnsignedBounds::Min(()), 4)),
+            TestUnsignedBounds::read_xdr(&[0, 0, 0, 0])
+        );
+        assert_eq!(
+            Ok((TestUnsignedBounds::Max(()), 4)),
+            TestUnsignedBounds::read_xdr(&[255, 255, 255, 255])
+        );
+        assert_eq!(Ok(min), crate::ser::to_xdr_bytes(&TestUnionBounds::Min(())));
+    }
+
+    type TestHash = Vec<u8>;
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    struct TestQualifiedTypes {
+        #[array(var = 4)]
+        name: std::string::String,
+        #[array(fixed = 2)]
+        hash: TestHash,
+        #[array(var = 2)]
+        memo: ::std::vec::Vec<u8>,
+        maybe: Option<Vec<u8>>,
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    enum TestQualifiedUnion {
+        #[discriminant(value = "0")]
+        Items(std::vec::Vec<Option<u32>>),
+        #[discriminant(value = "1")]
+        Hash(TestHash),
+    }
+
+    #[test]
+    fn test_qualified_types() {
+        let to_des: Vec<u8> = vec![
+            0, 0, 0, 2, 104, 105, 0, 0, 1, 2, 0, 0, 0, 0, 0, 1, 3, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1,
+            4, 0, 0, 0,
+        ];
+        let result = TestQualifiedTypes::read_xdr(&to_des);
+        assert_eq!(
+            Ok((
+                TestQualifiedTypes {
+                    name: "hi".to_string(),
+                    hash: vec![1, 2],
+                    memo: vec![3],
+                    maybe: Some(vec![4]),
+                },
+                32
+            )),
+            result
+        );
+        let forwarded = crate::ser::to_xdr_bytes(&result.unwrap().0).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        let json = r#"{"name":"hi","hash":"0102","memo":"Aw==","maybe":["BA=="]}"#;
+        let result: TestQualifiedTypes = read_json_string(json.to_string()).unwrap();
+        assert_eq!(json, crate::ser::to_json_string(&result).unwrap());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 5];
+        assert_eq!(
+            Ok((TestQualifiedUnion::Items(vec![Some(5)]), 16)),
+            TestQualifiedUnion::read_xdr(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0, 0, 0, 1, 6, 0, 0, 0];
+        assert_eq!(
+            Ok((TestQualifiedUnion::Hash(vec![6]), 12)),
+            TestQualifiedUnion::read_xdr(&to_des)
+        );
+    }
+
+    #[derive(XDRIn, XDROut, Debug, PartialEq)]
+    struct TestNestedBounds {
+        #[array(var = 2)]
+        signers: Vec<VarOpaque<2>>,
+        assets: VarArray<Option<u32>, 2>,
+        #[array(var = 2)]
+        memo: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn test_nested_bounds() {
+        let to_des: Vec<u8> = vec![
+            0, 0, 0, 2, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 2, 2, 3, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1, 0,
+            0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 5, 0, 0, 0,
+        ];
+        let result = TestNestedBounds::read_xdr(&to_des);
+        assert_eq!(
+            Ok((
+                TestNestedBounds {
+                    signers: vec![
+                        VarOpaque::new(vec![1]).unwrap(),
+                        VarOpaque::new(vec![2, 3]).unwrap()
+                    ],
+                    assets: VarArray::new(vec![Some(4), None]).unwrap(),
+                    memo: Some(vec![5]),
+                },
+                48
+            )),
+            result
+        );
+        let forwarded = crate::ser::to_xdr_bytes(&result.unwrap().0).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 2, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 3, 2, 3, 4, 0];
+        let err = TestNestedBounds::read_xdr(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!("signers[1]", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 3, 1, 2, 3, 0];
+        let err = TestNestedBounds::read_xdr(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!("memo", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            Ok((
+                TestNestedBounds {
+                    signers: vec![],
+                    assets: VarArray::default(),
+                    memo: None,
+                },
+                12
+            )),
+            TestNestedBounds::read_xdr(&to_des)
+        );
+    }
+
+    #[test]
+    fn test_nested_bounds_json() {
+        let json = r#"{"signers":["AQ==","AgM="],"assets":[[4],[]],"memo":["BQ=="]}"#;
+        let result: TestNestedBounds = read_json_string(json.to_string()).unwrap();
+        assert_eq!(Some(vec![5]), result.memo);
+        assert_eq!(json, crate::ser::to_json_string(&result).unwrap());
+
+        let err = TestNestedBounds::read_json(
+            json::parse(r#"{"signers":["AQ==","AgME"],"assets":[],"memo":[]}"#).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!("signers[1]", err.path());
+
+        let err = TestNestedBounds::read_json(
+            json::parse(r#"{"signers":[],"assets":[],"memo":["AQID"]}"#).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!("memo", err.path());
+    }
+
+    #[test]
+    fn test_union_discriminant_error() {
+        let to_des_1: Vec<u8> = vec![0, 0, 0, 0, 0x3f, 0x80, 0, 0, 0, 0, 0, 2];
+        assert_eq!(
+            Err(Error::invalid_enum_value()),
+            TestUnionDiscriminant::read_xdr(&to_des_1)
+        );
+
+        let to_des_2: Vec<u8> = vec![255, 255, 255, 255, 0x3f, 0x80];
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            TestUnionDiscriminant::read_xdr(&to_des_2)
+        );
+
+        let to_des = r#"{"type":-1,"data": "asdf"}"#.to_string();
+        let result: Result<TestUnionDiscriminant, Error> = read_json_string(to_des);
+        assert_eq!(Err(Error::unsigned_integer_bad_format()), result);
+    }
+
+    // Hands out a single byte per read call, like a slow socket.
+    struct ByteReader {
+        bytes: Vec<u8>,
+        position: usize,
+    }
+
+    impl Read for ByteReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.position == self.bytes.len() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.bytes[self.position];
+            self.position += 1;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_reader_stream() {
+        let source = ByteReader {
+            bytes: vec![
+                0x3f, 0x80, 0, 0, 0, 0, 0, 2, 0, 0, 0, 5, 104, 101, 108, 108, 111, 0, 0, 0, 0, 0,
+                0, 1, 0, 0, 0, 0, 0, 0, 0, 2,
+            ],
+            position: 0,
+        };
+        let mut reader = XDRReader::new(source);
+        let first = TestStruct::read_xdr_from(&mut reader).unwrap();
+        assert_eq!((TestStruct { one: 1.0, two: 2 }, 8), first);
+        let second = String::read_xdr_from(&mut reader).unwrap();
+        assert_eq!(("hello".to_string(), 12), second);
+        assert_eq!(20, reader.position());
+        let third = TestUnion::read_xdr_from(&mut reader).unwrap();
+        assert_eq!(
+            (TestUnion::Second(TestStruct { one: 0.0, two: 2 }), 12),
+            third
+        );
+        assert_eq!(32, reader.position());
+    }
+
+    #[test]
+    fn test_reader_padding_consumed() {
+        let to_des: Vec<u8> = vec![3, 3, 3, 4, 1, 0, 0, 0, 0, 0, 0, 9];
+        let mut reader = XDRReader::new(&to_des[..]);
+        let opaque = TestFixedOpaquePadding::read_xdr_from(&mut reader).unwrap();
+        assert_eq!(8, reader.position());
+        let next = u32::read_xdr_from(&mut reader).unwrap();
+        assert_eq!(
+            (
+                TestFixedOpaquePadding {
+                    opaque: vec![3, 3, 3, 4, 1]
+                },
+                8
+            ),
+            opaque
+        );
+        assert_eq!((9, 4), next);
+    }
+
+    #[test]
+    fn test_reader_var_opaque_missing_padding() {
+        let source = ByteReader {
+            bytes: vec![0, 0, 0, 5, 3, 3, 3, 4, 1, 0],
+            position: 0,
+        };
+        let result = Vec::<u8>::read_xdr_from(&mut XDRReader::new(source));
+        assert_eq!(Err(Error::unexpected_eof()), result);
+    }
+
+    #[test]
+    fn test_strict_padding() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 104, 0, 1, 0];
+        assert_eq!(Ok((vec![104], 8)), Vec::<u8>::read_xdr(&to_des));
+        assert_eq!(Ok(("h".to_string(), 8)), String::read_xdr(&to_des));
+        let err = Vec::<u8>::read_xdr_strict(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidPadding, err.kind());
+        assert_eq!(Some(5), err.offset());
+        assert_eq!(
+            Err(Error::invalid_padding()),
+            String::read_xdr_strict(&to_des)
+        );
+
+        let to_des: Vec<u8> = vec![3, 3, 3, 4, 1, 0, 0, 1];
+        assert_eq!(
+            Err(Error::invalid_padding()),
+            TestFixedOpaquePadding::read_xdr_strict(&to_des)
+        );
+        let to_des: Vec<u8> = vec![3, 3, 3, 4, 1, 0, 0, 0];
+        assert_eq!(
+            Ok((
+                TestFixedOpaquePadding {
+                    opaque: vec![3, 3, 3, 4, 1]
+                },
+                8
+            )),
+            TestFixedOpaquePadding::read_xdr_strict(&to_des)
+        );
+    }
+
+    #[test]
+    fn test_from_xdr_bytes() {
+        let to_des: Vec<u8> = vec![0x3f, 0x80, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0];
+        let expected = TestStruct { one: 1.0, two: 2 };
+        assert_eq!(Ok(expected), from_xdr_bytes::<TestStruct>(&to_des));
+        assert_eq!(
+            Err(Error::trailing_bytes()),
+            from_xdr_exact::<TestStruct>(&to_des)
+        );
+        assert_eq!(
+            Ok(TestStruct { one: 1.0, two: 2 }),
+            from_xdr_exact(&to_des[..8])
+        );
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            from_xdr_exact::<TestStruct>(&to_des[..6])
+        );
+    }
+
+    #[test]
+    fn test_strict_trailing_bytes() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 0];
+        assert_eq!(Ok((1, 4)), u32::read_xdr(&to_des));
+        let err = u32::read_xdr_strict(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::TrailingBytes, err.kind());
+        assert_eq!(Some(4), err.offset());
+
+        let mut reader = XDRReader::new(&to_des[..4]);
+        assert_eq!(Ok((1, 4)), u32::read_xdr_from(&mut reader));
+        assert_eq!(Ok(()), reader.expect_end());
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    #[xdr(transparent)]
+    pub struct ID {
+        #[array(fixed = 32)]
+        pub t: Vec<u8>,
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    pub struct User {
+        pub id: ID,
+
+        #[array(var = 80)]
+        pub name: String,
+    }
+
+    #[test]
+    fn test_array_complex() {
+        let to_des = r#"[{"id":"0000000000000000000000000000000000000000000000000000000000000000","name":"sam"}]"#.to_string();
+        let result: Vec<User> = read_json_string(to_des).unwrap();
+        let expected: Vec<User> = vec![User {
+            id: ID { t: vec![0; 32] },
+            name: "sam".to_string(),
+        }];
+        assert_eq!(expected, result);
+    }
+
+    // Decodes every strict prefix of a valid encoding and expects each one to
+    // be reported as truncated rather than panicking or succeeding.
+    fn assert_truncated<T: XDRIn>(encoded: &[u8]) {
+        assert!(T::read_xdr(encoded).is_ok());
+        for len in 0..encoded.len() {
+            assert_eq!(
+                Some(Error::unexpected_eof()),
+                T::read_xdr(&encoded[..len]).err(),
+                "prefix of length {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_primitives() {
+        assert_truncated::<bool>(&[0, 0, 0, 1]);
+        assert_truncated::<i32>(&[255, 255, 255, 255]);
+        assert_truncated::<u32>(&[255, 255, 255, 255]);
+        assert_truncated::<i64>(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_truncated::<u64>(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_truncated::<f32>(&[0x3f, 0x80, 0, 0]);
+        assert_truncated::<f64>(&[0x3f, 0xf0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_truncated_containers() {
+        assert_truncated::<String>(&[0, 0, 0, 5, 104, 101, 108, 108, 111, 0, 0, 0]);
+        assert_truncated::<Vec<u8>>(&[0, 0, 0, 5, 3, 3, 3, 4, 1, 0, 0, 0]);
+        assert_truncated::<Vec<u32>>(&[0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 3]);
+        assert_truncated::<Option<u32>>(&[0, 0, 0, 1, 0, 0, 0, 3]);
+        assert_truncated::<Box<u32>>(&[0, 0, 0, 3]);
+        assert_truncated::<Rc<u32>>(&[0, 0, 0, 3]);
+        assert_truncated::<Arc<u32>>(&[0, 0, 0, 3]);
+    }
+
+    #[test]
+    fn test_truncated_derived() {
+        assert_truncated::<TestStruct>(&[0x3f, 0x80, 0, 0, 0, 0, 0, 2]);
+        assert_truncated::<TestEnum>(&[0, 0, 0, 2]);
+        assert_truncated::<TestUnion>(&[0, 0, 0, 1, 0x3f, 0x80, 0, 0, 0, 0, 0, 2]);
+        assert_truncated::<TestUnion>(&[0, 0, 0, 2]);
+        assert_truncated::<TestUnionDiscriminant>(&[255, 255, 255, 255, 0, 0, 0, 3]);
+        assert_truncated::<TestFixedOpaquePadding>(&[3, 3, 3, 4, 1, 0, 0, 0]);
+        assert_truncated::<TestFixedArray>(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 3]);
+        assert_truncated::<TestVarArray>(&[0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0, 6]);
+        assert_truncated::<TestStringLength>(&[0, 0, 0, 5, 104, 101, 108, 108, 111, 0, 0, 0]);
+        assert_truncated::<User>(&[
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32, 0, 0, 0, 3, 115, 97, 109, 0,
+        ]);
+    }
+
+    #[test]
+    fn test_truncated_hostile_length() {
+        let to_des: Vec<u8> = vec![255, 255, 255, 255, 1, 2, 3];
+        let unbounded = || XDRReader::with_limits(&to_des[..], DecodeLimits::unbounded());
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            Vec::<u8>::read_xdr_from(&mut unbounded())
+        );
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            String::read_xdr_from(&mut unbounded())
+        );
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            Vec::<u32>::read_xdr_from(&mut unbounded())
+        );
+        assert_eq!(
+            Err(Error::allocation_limit_exceeded()),
+            Vec::<u8>::read_xdr(&to_des)
+        );
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestNode {
+        value: u32,
+        next: Option<Box<TestNode>>,
+    }
+
+    fn node_chain(length: usize) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for _ in 0..length {
+            encoded.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1]);
+        }
+        encoded.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0]);
+        encoded
+    }
+
+    #[test]
+    fn test_limits_default() {
+        let to_des = node_chain(100);
+        assert!(TestNode::read_xdr(&to_des).is_ok());
+    }
+
+    #[test]
+    fn test_limits_default_bounded() {
+        let to_des: Vec<u8> = vec![255, 255, 255, 255];
+        assert_eq!(
+            Some(Error::element_limit_exceeded()),
+            Vec::<()>::read_xdr(&to_des).err()
+        );
+        let to_des: Vec<u8> = vec![0, 64, 0, 0];
+        assert_eq!(
+            Some(Error::allocation_limit_exceeded()),
+            Vec::<[u64; 4]>::read_xdr(&to_des).err()
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 3];
+        let mut reader = XDRReader::with_limits(&to_des[..], DecodeLimits::unbounded());
+        assert_eq!(
+            Ok((vec![(), (), ()], 4)),
+            Vec::<()>::read_xdr_from(&mut reader)
+        );
+    }
+
+    #[test]
+    fn test_limits_depth() {
+        let to_des = node_chain(10_000);
+        assert_eq!(
+            Some(Error::depth_limit_exceeded()),
+            TestNode::read_xdr(&to_des).err()
+        );
+
+        let limits = DecodeLimits {
+            max_depth: 8,
+            ..DecodeLimits::default()
+        };
+        let to_des = node_chain(4);
+        let mut reader = XDRReader::with_limits(&to_des[..], limits.clone());
+        assert!(TestNode::read_xdr_from(&mut reader).is_ok());
+        let to_des = node_chain(5);
+        let mut reader = XDRReader::with_limits(&to_des[..], limits);
+        assert_eq!(
+            Some(Error::depth_limit_exceeded()),
+            TestNode::read_xdr_from(&mut reader).err()
+        );
+    }
+
+    #[test]
+    fn test_limits_elements() {
+        let limits = DecodeLimits {
+            max_elements: 1000,
+            ..DecodeLimits::default()
+        };
+        let to_des: Vec<u8> = vec![255, 255, 255, 255, 0, 0, 0, 0];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits.clone());
+        assert_eq!(
+            Err(Error::element_limit_exceeded()),
+            Vec::<()>::read_xdr_from(&mut reader)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 3, 232];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits);
+        assert_eq!(
+            Err(Error::element_limit_exceeded()),
+            Vec::<Vec<()>>::read_xdr_from(&mut reader)
+        );
+    }
+
+    #[test]
+    fn test_limits_bytes() {
+        let limits = DecodeLimits {
+            max_bytes: 16,
+            ..DecodeLimits::default()
+        };
+        let to_des: Vec<u8> = vec![0, 0, 0, 16, 1, 2, 3, 4];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits.clone());
+        assert_eq!(
+            Err(Error::unexpected_eof()),
+            Vec::<u8>::read_xdr_from(&mut reader)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 17, 1, 2, 3, 4];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits.clone());
+        assert_eq!(
+            Err(Error::allocation_limit_exceeded()),
+            String::read_xdr_from(&mut reader)
+        );
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 5, 0, 0, 0, 1];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits.clone());
+        assert_eq!(
+            Err(Error::allocation_limit_exceeded()),
+            Vec::<u32>::read_xdr_from(&mut reader)
+        );
+
+        let to_des: Vec<u8> = vec![
+            0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 5, 104, 101, 108, 108, 111,
+        ];
+        let mut reader = XDRReader::with_limits(&to_des[..], limits);
+        assert_eq!(
+            Err(Error::allocation_limit_exceeded()),
+            TestUser::read_xdr_from(&mut reader)
+        );
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestUser {
+        #[array(var = 4)]
+        ids: Vec<u32>,
+        name: String,
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestItem {
+        id: u32,
+        kind: TestEnum,
+    }
+
+    #[derive(XDRIn, Debug, PartialEq)]
+    struct TestOrder {
+        flag: bool,
+        #[array(var = 4)]
+        items: Vec<TestItem>,
+    }
+
+    #[test]
+    fn test_error_context() {
+        let to_des: Vec<u8> = vec![
+            0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 7, 0, 0, 0, 1, 0, 0, 0, 8, 0, 0, 0, 9,
+        ];
+        let err = TestOrder::read_xdr(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidEnumValue, err.kind());
+        assert_eq!(Some(20), err.offset());
+        assert_eq!("items[1].kind", err.path());
+        assert_eq!(
+            "InvalidEnumValue at items[1].kind (byte offset 20)",
+            err.to_string()
+        );
+
+        let err = TestOrder::read_xdr(&to_des[..22]).unwrap_err();
+        assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(Some(22), err.offset());
+        assert_eq!("items[1].kind", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 2];
+        let err = TestOrder::read_xdr(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::BoolBadFormat, err.kind());
+        assert_eq!(Some(0), err.offset());
+        assert_eq!("flag", err.path());
+    }
+
+    #[test]
+    fn test_error_context_json() {
+        let err = TestOrder::read_json(
+            json::parse(
+                r#"{"flag": true, "items": [{"id": 1, "kind": 1}, {"id": "x", "kind": 1}]}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(&ErrorKind::UnsignedIntegerBadFormat, err.kind());
+        assert_eq!(None, err.offset());
+        assert_eq!("items[1].id", err.path());
+
+        let err =
+            TestOrder::read_json(json::parse(r#"{"flag": true, "items": [{"id": 1}]}"#).unwrap())
+                .unwrap_err();
+        assert_eq!(&ErrorKind::InvalidJson, err.kind());
+        assert_eq!("items[0].kind", err.path());
+    }
+
+    #[derive(XDRInBorrowed, XDROut, Debug, PartialEq)]
+    struct TestBorrowed<'a> {
+        id: u32,
+        #[array(var = 4)]
+        memo: &'a [u8],
+        name: &'a str,
+        #[array(fixed = 2)]
+        hash: Cow<'a, [u8]>,
+        key: TestBorrowedKey<'a>,
+    }
+
+    #[derive(XDRInBorrowed, XDROut, Debug, PartialEq)]
+    struct TestBorrowedKey<'a>(&'a [u8]);
+
+    #[test]
+    fn test_borrowed() {
+        let to_des: Vec<u8> = vec![
+            0, 0, 0, 7, 0, 0, 0, 3, 1, 2, 3, 0, 0, 0, 0, 2, 104, 105, 0, 0, 9, 8, 0, 0, 0, 0, 0, 1,
+            5, 0, 0, 0,
+        ];
+        let result = TestBorrowed::read_xdr_borrowed(&to_des);
+        assert_eq!(
+            Ok((
+                TestBorrowed {
+                    id: 7,
+                    memo: &[1, 2, 3],
+                    name: "hi",
+                    hash: Cow::Borrowed(&[9, 8]),
+                    key: TestBorrowedKey(&[5]),
+                },
+                32
+            )),
+            result
+        );
+        let result = result.unwrap().0;
+        assert_eq!(to_des[8..].as_ptr(), result.memo.as_ptr());
+        assert_eq!(to_des[16..].as_ptr(), result.name.as_ptr());
+        assert!(matches!(result.hash, Cow::Borrowed(_)));
+        let forwarded = crate::ser::to_xdr_bytes(&result).unwrap();
+        assert_eq!(to_des, forwarded);
+
+        assert_eq!(Ok((7, 4)), u32::read_xdr_borrowed(&to_des));
+        assert_eq!(
+            Ok((&[1, 2, 3][..], 8)),
+            <&[u8]>::read_xdr_borrowed(&to_des[4..])
+        );
+    }
+
+    #[test]
+    fn test_borrowed_errors() {
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0, 0, 5, 1, 2, 3, 4, 5, 0, 0, 0];
+        let err = TestBorrowed::read_xdr_borrowed(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!(Some(4), err.offset());
+        assert_eq!("memo", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 1, 0xff, 0, 0, 0];
+        let err = TestBorrowed::read_xdr_borrowed(&to_des).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Utf8Error(_)));
+        assert_eq!(Some(12), err.offset());
+        assert_eq!("name", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 7, 0, 0, 0, 3, 1, 2];
+        let err = TestBorrowed::read_xdr_borrowed(&to_des).unwrap_err();
+        assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(Some(10), err.offset());
+        assert_eq!("memo", err.path());
+
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 104, 0, 1, 0];
+        assert_eq!(Ok(("h", 8)), <&str>::read_xdr_borrowed(&to_des));
+        assert_eq!(
+            Err(Error::invalid_padding()),
+            <&str>::read_xdr_borrowed_strict(&to_des)
+        );
+        let to_des: Vec<u8> = vec![0, 0, 0, 1, 104, 0, 0, 0, 0];
+        assert_eq!(
+            Err(Error::trailing_bytes()),
+            <Cow<str>>::read_xdr_borrowed_strict(&to_des)
+        );
     }
 }
@@ -9,6 +9,7 @@ pub enum ErrorKind {
     UnsignedHyperBadFormat,
     FloatBadFormat,
     DoubleBadFormat,
+    QuadBadFormat,
     StringBadFormat,
 
     FixedArrayWrongSize,
@@ -17,117 +18,202 @@ pub enum ErrorKind {
 
     BadArraySize,
     InvalidPadding,
+    TrailingBytes,
 
     InvalidJson,
 
+    UnexpectedEof,
+    BufferTooSmall,
+
+    AllocationLimitExceeded,
+    ElementLimitExceeded,
+    DepthLimitExceeded,
+
+    InvalidSchema(String),
+
     Utf8Error(std::str::Utf8Error),
     IOError(std::io::ErrorKind),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// Error raised while encoding or decoding.
+///
+/// Decoding errors additionally carry the byte offset in the input and the
+/// field path (e.g. `tx.operations[3].body.amount`) at which they occurred,
+/// when known. Errors compare equal when their kinds match; the location is
+/// informational only.
+#[derive(Debug)]
 pub struct Error {
     kind: ErrorKind,
+    offset: Option<u64>,
+    // Innermost segment first, as segments are added while unwinding.
+    path: Vec<PathSegment>,
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.kind() {
-            _ => write!(f, "{:?}", self),
+        write!(f, "{:?}", self.kind)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path())?;
         }
+        if let Some(offset) = self.offset {
+            write!(f, " (byte offset {})", offset)?;
+        }
+        Ok(())
     }
 }
 
 impl Error {
     fn from_kind(kind: ErrorKind) -> Self {
-        Error { kind }
+        Error {
+            kind,
+            offset: None,
+            path: Vec::new(),
+        }
     }
 
-    fn kind(&self) -> &ErrorKind {
+    pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
 
-    pub fn bool_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::BoolBadFormat,
+    /// Byte offset in the XDR input at which decoding failed.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Dotted path of the field that failed to decode, empty at the top level.
+    pub fn path(&self) -> String {
+        let mut result = String::new();
+        for segment in self.path.iter().rev() {
+            match segment {
+                PathSegment::Field(name) => {
+                    if !result.is_empty() {
+                        result.push('.');
+                    }
+                    result.push_str(name);
+                }
+                PathSegment::Index(index) => result.push_str(&format!("[{}]", index)),
+            }
         }
+        result
+    }
+
+    /// Records where the error occurred, keeping the innermost offset if one
+    /// was already set.
+    pub fn at_offset(mut self, offset: u64) -> Self {
+        self.offset.get_or_insert(offset);
+        self
+    }
+
+    /// Prefixes the path with the struct field the error occurred in.
+    pub fn in_field(mut self, name: &str) -> Self {
+        self.path.push(PathSegment::Field(name.to_string()));
+        self
+    }
+
+    /// Prefixes the path with the array index the error occurred at.
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(PathSegment::Index(index));
+        self
+    }
+
+    pub fn bool_bad_format() -> Self {
+        Error::from_kind(ErrorKind::BoolBadFormat)
     }
 
     pub fn integer_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::IntegerBadFormat,
-        }
+        Error::from_kind(ErrorKind::IntegerBadFormat)
     }
 
     pub fn unsigned_integer_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::UnsignedIntegerBadFormat,
-        }
+        Error::from_kind(ErrorKind::UnsignedIntegerBadFormat)
     }
 
     pub fn hyper_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::HyperBadFormat,
-        }
+        Error::from_kind(ErrorKind::HyperBadFormat)
     }
 
     pub fn unsigned_hyper_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::UnsignedHyperBadFormat,
-        }
+        Error::from_kind(ErrorKind::UnsignedHyperBadFormat)
     }
 
     pub fn float_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::FloatBadFormat,
-        }
+        Error::from_kind(ErrorKind::FloatBadFormat)
     }
 
     pub fn double_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::DoubleBadFormat,
-        }
+        Error::from_kind(ErrorKind::DoubleBadFormat)
+    }
+
+    pub fn quad_bad_format() -> Self {
+        Error::from_kind(ErrorKind::QuadBadFormat)
     }
 
     pub fn string_bad_format() -> Self {
-        Error {
-            kind: ErrorKind::StringBadFormat,
-        }
+        Error::from_kind(ErrorKind::StringBadFormat)
     }
 
     pub fn fixed_array_wrong_size() -> Self {
-        Error {
-            kind: ErrorKind::FixedArrayWrongSize,
-        }
+        Error::from_kind(ErrorKind::FixedArrayWrongSize)
     }
 
     pub fn var_array_wrong_size() -> Self {
-        Error {
-            kind: ErrorKind::VarArrayWrongSize,
-        }
+        Error::from_kind(ErrorKind::VarArrayWrongSize)
     }
 
     pub fn invalid_enum_value() -> Self {
-        Error {
-            kind: ErrorKind::InvalidEnumValue,
-        }
+        Error::from_kind(ErrorKind::InvalidEnumValue)
     }
 
     pub fn bad_array_size() -> Self {
-        Error {
-            kind: ErrorKind::BadArraySize,
-        }
+        Error::from_kind(ErrorKind::BadArraySize)
     }
 
     pub fn invalid_padding() -> Self {
-        Error {
-            kind: ErrorKind::InvalidPadding,
-        }
+        Error::from_kind(ErrorKind::InvalidPadding)
+    }
+
+    pub fn trailing_bytes() -> Self {
+        Error::from_kind(ErrorKind::TrailingBytes)
     }
 
     pub fn invalid_json() -> Self {
-        Error {
-            kind: ErrorKind::InvalidJson,
-        }
+        Error::from_kind(ErrorKind::InvalidJson)
+    }
+
+    pub fn unexpected_eof() -> Self {
+        Error::from_kind(ErrorKind::UnexpectedEof)
+    }
+
+    pub fn buffer_too_small() -> Self {
+        Error::from_kind(ErrorKind::BufferTooSmall)
+    }
+
+    pub fn invalid_schema(message: String) -> Self {
+        Error::from_kind(ErrorKind::InvalidSchema(message))
+    }
+
+    pub fn allocation_limit_exceeded() -> Self {
+        Error::from_kind(ErrorKind::AllocationLimitExceeded)
+    }
+
+    pub fn element_limit_exceeded() -> Self {
+        Error::from_kind(ErrorKind::ElementLimitExceeded)
+    }
+
+    pub fn depth_limit_exceeded() -> Self {
+        Error::from_kind(ErrorKind::DepthLimitExceeded)
     }
 }
 
@@ -1,6 +1,16 @@
+pub mod codegen;
 pub mod de;
 pub mod error;
+pub mod runtime;
+pub mod schema;
 pub mod ser;
+pub mod types;
+
+/// The `json` crate, which the derived `read_json` implementations use.
+pub use json;
+
+// Lets the derives' `::xdr_rs_serialize` paths resolve within this crate.
+extern crate self as xdr_rs_serialize;
 
 #[cfg(test)]
 #[macro_use]
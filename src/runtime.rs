@@ -0,0 +1,925 @@
+use crate::de::{
+    read_fixed_opaque, read_fixed_opaque_json, read_var_opaque, read_var_opaque_json,
+    read_var_string, read_var_string_json, Read, XDRIn, XDRReader,
+};
+use crate::error::Error;
+use crate::schema::{Constants, Declaration, DeclarationKind, Definition, Schema, Type, Value};
+use crate::ser::{
+    write_fixed_array, write_fixed_opaque, write_fixed_opaque_json, Write, XDRFixedOut, XDROut,
+};
+use crate::types::Quad;
+use json::JsonValue;
+use std::collections::HashMap;
+
+/// Runtime description of an XDR type, resolved from a `Schema` so that
+/// values can be handled without a compile-time Rust type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeDesc {
+    Int,
+    UnsignedInt,
+    Hyper,
+    UnsignedHyper,
+    Float,
+    Double,
+    Quadruple,
+    Bool,
+    Void,
+    FixedOpaque(u32),
+    /// Variable length opaque data with its maximum length, if any.
+    VarOpaque(Option<u32>),
+    String(Option<u32>),
+    FixedArray(Box<TypeDesc>, u32),
+    VarArray(Box<TypeDesc>, Option<u32>),
+    Optional(Box<TypeDesc>),
+    Enum(Vec<(String, i32)>),
+    Struct(Vec<(String, TypeDesc)>),
+    Union(UnionDesc),
+    /// Reference to a type defined in the `RuntimeSchema`.
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnionDesc {
+    pub discriminant: Box<TypeDesc>,
+    /// One arm per case label, as the label's value, the arm's declared
+    /// name (empty for void) and its type.
+    pub arms: Vec<(i32, String, TypeDesc)>,
+    pub default: Option<Box<TypeDesc>>,
+}
+
+impl UnionDesc {
+    /// The type of the arm selected by `discriminant`, if any.
+    pub fn arm(&self, discriminant: i32) -> Option<&TypeDesc> {
+        match self.arms.iter().find(|arm| arm.0 == discriminant) {
+            Some(arm) => Some(&arm.2),
+            None => self.default.as_deref(),
+        }
+    }
+}
+
+/// A decoded XDR value whose type is only known at runtime.
+///
+/// Values carry enough of their type to be encoded again without the
+/// schema, so `write_xdr` reproduces the input they were decoded from and
+/// `write_json` matches the derived output for the same type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum XdrValue {
+    Int(i32),
+    UnsignedInt(u32),
+    Hyper(i64),
+    UnsignedHyper(u64),
+    Float(f32),
+    Double(f64),
+    Quadruple(Quad),
+    Bool(bool),
+    Void,
+    Enum(i32),
+    FixedOpaque(Vec<u8>),
+    VarOpaque(Vec<u8>),
+    String(String),
+    FixedArray(Vec<XdrValue>),
+    VarArray(Vec<XdrValue>),
+    Optional(Option<Box<XdrValue>>),
+    /// Members in declaration order, with their names.
+    Struct(Vec<(String, XdrValue)>),
+    /// The discriminant, typed as the union's switch, and the arm's value.
+    Union(Box<XdrValue>, Box<XdrValue>),
+}
+
+/// The named types of a schema, resolved for use at runtime.
+///
+/// Values are transcoded between XDR and the same JSON layout that
+/// `write_json` produces for the types `codegen` generates from the schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeSchema {
+    types: HashMap<String, TypeDesc>,
+}
+
+impl RuntimeSchema {
+    pub fn new(schema: &Schema) -> Result<Self, Error> {
+        let constants = schema.constants()?;
+        let mut types = HashMap::new();
+        for definition in schema.definitions.iter() {
+            match definition {
+                Definition::Const(_, _) => {}
+                Definition::Typedef(declaration) => {
+                    types.insert(
+                        declaration.name.clone(),
+                        describe_declaration(schema, &constants, declaration)?,
+                    );
+                }
+                Definition::Type(name, ty) => {
+                    types.insert(name.clone(), describe_type(schema, &constants, ty)?);
+                }
+            }
+        }
+        Ok(RuntimeSchema { types })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypeDesc> {
+        self.types.get(name)
+    }
+
+    /// Follows `Named` references until reaching a concrete description.
+    pub fn resolve<'a>(&'a self, ty: &'a TypeDesc) -> Result<&'a TypeDesc, Error> {
+        let mut current = ty;
+        // A longer chain than there are types must be a cycle.
+        for _ in 0..=self.types.len() {
+            match current {
+                TypeDesc::Named(name) => {
+                    current = self
+                        .types
+                        .get(name)
+                        .ok_or_else(|| Error::invalid_schema(format!("unknown type {}", name)))?;
+                }
+                _ => return Ok(current),
+            }
+        }
+        Err(Error::invalid_schema("typedef cycle".to_string()))
+    }
+
+    /// Decodes a value of type `ty` from `reader`.
+    pub fn read_xdr_from<R: Read>(
+        &self,
+        ty: &TypeDesc,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(XdrValue, u64), Error> {
+        Ok(match self.resolve(ty)? {
+            TypeDesc::Int => wrap(i32::read_xdr_from(reader)?, XdrValue::Int),
+            TypeDesc::UnsignedInt => wrap(u32::read_xdr_from(reader)?, XdrValue::UnsignedInt),
+            TypeDesc::Hyper => wrap(i64::read_xdr_from(reader)?, XdrValue::Hyper),
+            TypeDesc::UnsignedHyper => wrap(u64::read_xdr_from(reader)?, XdrValue::UnsignedHyper),
+            TypeDesc::Float => wrap(f32::read_xdr_from(reader)?, XdrValue::Float),
+            TypeDesc::Double => wrap(f64::read_xdr_from(reader)?, XdrValue::Double),
+            TypeDesc::Quadruple => wrap(Quad::read_xdr_from(reader)?, XdrValue::Quadruple),
+            TypeDesc::Bool => wrap(bool::read_xdr_from(reader)?, XdrValue::Bool),
+            TypeDesc::Void => (XdrValue::Void, 0),
+            TypeDesc::FixedOpaque(size) => {
+                wrap(read_fixed_opaque(*size, reader)?, XdrValue::FixedOpaque)
+            }
+            TypeDesc::VarOpaque(max) => wrap(
+                read_var_opaque(max.unwrap_or(u32::MAX), reader)?,
+                XdrValue::VarOpaque,
+            ),
+            TypeDesc::String(max) => wrap(
+                read_var_string(max.unwrap_or(u32::MAX), reader)?,
+                XdrValue::String,
+            ),
+            TypeDesc::FixedArray(element, size) => wrap(
+                self.read_array(element, *size, reader)?,
+                XdrValue::FixedArray,
+            ),
+            TypeDesc::VarArray(element, max) => {
+                let length = u32::read_xdr_from(reader)?.0;
+                if length > max.unwrap_or(u32::MAX) {
+                    return Err(Error::bad_array_size().at_offset(reader.position() - 4));
+                }
+                let (values, read) = self.read_array(element, length, reader)?;
+                (XdrValue::VarArray(values), read + 4)
+            }
+            TypeDesc::Optional(value) => {
+                if u32::read_xdr_from(reader)?.0 == 0 {
+                    (XdrValue::Optional(None), 4)
+                } else {
+                    let (value, read) =
+                        reader.nested(|reader| self.read_xdr_from(value, reader))?;
+                    (XdrValue::Optional(Some(Box::new(value))), read + 4)
+                }
+            }
+            TypeDesc::Enum(constants) => {
+                let value = i32::read_xdr_from(reader)?.0;
+                if !constants.iter().any(|c| c.1 == value) {
+                    return Err(Error::invalid_enum_value().at_offset(reader.position() - 4));
+                }
+                (XdrValue::Enum(value), 4)
+            }
+            TypeDesc::Struct(members) => {
+                let mut values = Vec::new();
+                let mut read = 0;
+                for (name, member) in members.iter() {
+                    let (value, member_read) = reader.field(name, |reader| {
+                        reader.nested(|reader| self.read_xdr_from(member, reader))
+                    })?;
+                    values.push((name.clone(), value));
+                    read += member_read;
+                }
+                (XdrValue::Struct(values), read)
+            }
+            TypeDesc::Union(union) => {
+                let (discriminant, _) = self.read_xdr_from(&union.discriminant, reader)?;
+                let arm = match union.arm(discriminant_value(&discriminant)?) {
+                    Some(arm) => arm,
+                    None => {
+                        return Err(Error::invalid_enum_value().at_offset(reader.position() - 4))
+                    }
+                };
+                let (data, read) = reader.nested(|reader| self.read_xdr_from(arm, reader))?;
+                (
+                    XdrValue::Union(Box::new(discriminant), Box::new(data)),
+                    read + 4,
+                )
+            }
+            TypeDesc::Named(_) => unreachable!("resolved above"),
+        })
+    }
+
+    fn read_array<R: Read>(
+        &self,
+        element: &TypeDesc,
+        size: u32,
+        reader: &mut XDRReader<R>,
+    ) -> Result<(Vec<XdrValue>, u64), Error> {
+        reader.reserve_elements(size as u64)?;
+        reader.nested(|reader| {
+            let mut values = Vec::new();
+            let mut read = 0;
+            for index in 0..size as usize {
+                let (value, element_read) =
+                    reader.element(index, |reader| self.read_xdr_from(element, reader))?;
+                values.push(value);
+                read += element_read;
+            }
+            Ok((values, read))
+        })
+    }
+
+    /// Reads a value of type `ty` from the JSON layout `write_json` produces.
+    pub fn read_json(&self, ty: &TypeDesc, jval: JsonValue) -> Result<XdrValue, Error> {
+        Ok(match self.resolve(ty)? {
+            TypeDesc::Int => XdrValue::Int(i32::read_json(jval)?),
+            TypeDesc::UnsignedInt => XdrValue::UnsignedInt(u32::read_json(jval)?),
+            TypeDesc::Hyper => XdrValue::Hyper(i64::read_json(jval)?),
+            TypeDesc::UnsignedHyper => XdrValue::UnsignedHyper(u64::read_json(jval)?),
+            TypeDesc::Float => XdrValue::Float(f32::read_json(jval)?),
+            TypeDesc::Double => XdrValue::Double(f64::read_json(jval)?),
+            TypeDesc::Quadruple => XdrValue::Quadruple(Quad::read_json(jval)?),
+            TypeDesc::Bool => XdrValue::Bool(bool::read_json(jval)?),
+            TypeDesc::Void => {
+                <()>::read_json(jval)?;
+                XdrValue::Void
+            }
+            TypeDesc::FixedOpaque(size) => {
+                XdrValue::FixedOpaque(read_fixed_opaque_json(*size, jval)?)
+            }
+            TypeDesc::VarOpaque(max) => {
+                XdrValue::VarOpaque(read_var_opaque_json(max.unwrap_or(u32::MAX), jval)?)
+            }
+            TypeDesc::String(max) => {
+                XdrValue::String(read_var_string_json(max.unwrap_or(u32::MAX), jval)?)
+            }
+            TypeDesc::FixedArray(element, size) => {
+                let values = json_array(jval)?;
+                if values.len() as u32 != *size {
+                    return Err(Error::bad_array_size());
+                }
+                XdrValue::FixedArray(self.read_json_array(element, values)?)
+            }
+            TypeDesc::VarArray(element, max) => {
+                let values = json_array(jval)?;
+                if values.len() as u32 > max.unwrap_or(u32::MAX) {
+                    return Err(Error::bad_array_size());
+                }
+                XdrValue::VarArray(self.read_json_array(element, values)?)
+            }
+            TypeDesc::Optional(value) => match jval {
+                JsonValue::Array(values) if values.len() <= 1 => match values.into_iter().next() {
+                    Some(inner) => {
+                        XdrValue::Optional(Some(Box::new(self.read_json(value, inner)?)))
+                    }
+                    None => XdrValue::Optional(None),
+                },
+                _ => return Err(Error::invalid_json()),
+            },
+            TypeDesc::Enum(constants) => {
+                let value = i32::read_json(jval)?;
+                if !constants.iter().any(|c| c.1 == value) {
+                    return Err(Error::invalid_enum_value());
+                }
+                XdrValue::Enum(value)
+            }
+            TypeDesc::Struct(members) => {
+                let mut obj = match jval {
+                    JsonValue::Object(obj) => obj,
+                    _ => return Err(Error::invalid_json()),
+                };
+                let mut values = Vec::new();
+                for (name, member) in members.iter() {
+                    let value = obj
+                        .remove(name)
+                        .ok_or_else(|| Error::invalid_json().in_field(name))?;
+                    let value = self
+                        .read_json(member, value)
+                        .map_err(|e| e.in_field(name))?;
+                    values.push((name.clone(), value));
+                }
+                XdrValue::Struct(values)
+            }
+            TypeDesc::Union(union) => {
+                let (value, data) = match jval {
+                    JsonValue::Object(mut obj) => {
+                        let value = obj.remove("type").ok_or_else(Error::invalid_json)?;
+                        let data = obj.remove("data").ok_or_else(Error::invalid_json)?;
+                        (value, data)
+                    }
+                    // Void arms of int unions may be given as just the
+                    // discriminant, as the derives accept.
+                    JsonValue::Number(_)
+                        if self.resolve(&union.discriminant)? == &TypeDesc::Int =>
+                    {
+                        (jval, JsonValue::from(""))
+                    }
+                    _ => return Err(Error::invalid_enum_value()),
+                };
+                let discriminant = self.read_json(&union.discriminant, value)?;
+                let arm = union
+                    .arm(discriminant_value(&discriminant)?)
+                    .ok_or_else(Error::invalid_enum_value)?;
+                XdrValue::Union(Box::new(discriminant), Box::new(self.read_json(arm, data)?))
+            }
+            TypeDesc::Named(_) => unreachable!("resolved above"),
+        })
+    }
+
+    fn read_json_array(
+        &self,
+        element: &TypeDesc,
+        values: Vec<JsonValue>,
+    ) -> Result<Vec<XdrValue>, Error> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                self.read_json(element, value)
+                    .map_err(|e| e.at_index(index))
+            })
+            .collect()
+    }
+
+    /// Decodes a value of type `ty` from `reader` and writes it out as JSON.
+    pub fn xdr_to_json<R: Read, W: Write + ?Sized>(
+        &self,
+        ty: &TypeDesc,
+        reader: &mut XDRReader<R>,
+        out: &mut W,
+    ) -> Result<u64, Error> {
+        self.read_xdr_from(ty, reader)?.0.write_json(out)
+    }
+
+    /// Reads a value of type `ty` from its JSON form and writes it out as
+    /// XDR.
+    pub fn json_to_xdr<W: Write + ?Sized>(
+        &self,
+        ty: &TypeDesc,
+        jval: JsonValue,
+        out: &mut W,
+    ) -> Result<u64, Error> {
+        self.read_json(ty, jval)?.write_xdr(out)
+    }
+}
+
+impl XDROut for XdrValue {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        match self {
+            XdrValue::Int(value) => value.write_xdr(out),
+            XdrValue::UnsignedInt(value) => value.write_xdr(out),
+            XdrValue::Hyper(value) => value.write_xdr(out),
+            XdrValue::UnsignedHyper(value) => value.write_xdr(out),
+            XdrValue::Float(value) => value.write_xdr(out),
+            XdrValue::Double(value) => value.write_xdr(out),
+            XdrValue::Quadruple(value) => value.write_xdr(out),
+            XdrValue::Bool(value) => value.write_xdr(out),
+            XdrValue::Void => ().write_xdr(out),
+            XdrValue::Enum(value) => value.write_xdr(out),
+            XdrValue::FixedOpaque(value) => write_fixed_opaque(value, value.len() as u32, out),
+            XdrValue::VarOpaque(value) => value.write_xdr(out),
+            XdrValue::String(value) => value.write_xdr(out),
+            XdrValue::FixedArray(values) => write_fixed_array(values, values.len() as u32, out),
+            XdrValue::VarArray(values) => values.write_xdr(out),
+            XdrValue::Optional(value) => value.write_xdr(out),
+            XdrValue::Struct(members) => {
+                let mut written = 0;
+                for (_, value) in members.iter() {
+                    written += value.write_xdr(out)?;
+                }
+                Ok(written)
+            }
+            XdrValue::Union(discriminant, data) => {
+                Ok(discriminant.write_xdr(out)? + data.write_xdr(out)?)
+            }
+        }
+    }
+
+    fn xdr_size(&self) -> u64 {
+        match self {
+            XdrValue::Int(value) => value.xdr_size(),
+            XdrValue::UnsignedInt(value) => value.xdr_size(),
+            XdrValue::Hyper(value) => value.xdr_size(),
+            XdrValue::UnsignedHyper(value) => value.xdr_size(),
+            XdrValue::Float(value) => value.xdr_size(),
+            XdrValue::Double(value) => value.xdr_size(),
+            XdrValue::Quadruple(value) => value.xdr_size(),
+            XdrValue::Bool(value) => value.xdr_size(),
+            XdrValue::Void => ().xdr_size(),
+            XdrValue::Enum(value) => value.xdr_size(),
+            XdrValue::FixedOpaque(value) => value.fixed_xdr_size(),
+            XdrValue::VarOpaque(value) => value.xdr_size(),
+            XdrValue::String(value) => value.xdr_size(),
+            XdrValue::FixedArray(values) => values.fixed_xdr_size(),
+            XdrValue::VarArray(values) => values.xdr_size(),
+            XdrValue::Optional(value) => value.xdr_size(),
+            XdrValue::Struct(members) => members.iter().map(|(_, value)| value.xdr_size()).sum(),
+            XdrValue::Union(discriminant, data) => discriminant.xdr_size() + data.xdr_size(),
+        }
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        match self {
+            XdrValue::Int(value) => value.write_json(out),
+            XdrValue::UnsignedInt(value) => value.write_json(out),
+            XdrValue::Hyper(value) => value.write_json(out),
+            XdrValue::UnsignedHyper(value) => value.write_json(out),
+            XdrValue::Float(value) => value.write_json(out),
+            XdrValue::Double(value) => value.write_json(out),
+            XdrValue::Quadruple(value) => value.write_json(out),
+            XdrValue::Bool(value) => value.write_json(out),
+            XdrValue::Void => ().write_json(out),
+            XdrValue::Enum(value) => value.write_json(out),
+            XdrValue::FixedOpaque(value) => write_fixed_opaque_json(value, value.len() as u32, out),
+            XdrValue::VarOpaque(value) => value.write_json(out),
+            XdrValue::String(value) => value.write_json(out),
+            XdrValue::FixedArray(values) | XdrValue::VarArray(values) => values.write_json(out),
+            XdrValue::Optional(value) => value.write_json(out),
+            XdrValue::Struct(members) => {
+                let mut written = write_literal("{", out)?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        written += write_literal(",", out)?;
+                    }
+                    written += write_literal(&format!("\"{}\":", name), out)?;
+                    written += value.write_json(out)?;
+                }
+                written += write_literal("}", out)?;
+                Ok(written)
+            }
+            XdrValue::Union(discriminant, data) => {
+                let mut written = write_literal("{\"type\":", out)?;
+                written += discriminant.write_json(out)?;
+                written += write_literal(",\"data\":", out)?;
+                written += data.write_json(out)?;
+                written += write_literal("}", out)?;
+                Ok(written)
+            }
+        }
+    }
+}
+
+// The `i32` a union's arms are keyed by, which is how every switch type is
+// encoded.
+fn discriminant_value(discriminant: &XdrValue) -> Result<i32, Error> {
+    match *discriminant {
+        XdrValue::Int(value) | XdrValue::Enum(value) => Ok(value),
+        XdrValue::UnsignedInt(value) => Ok(value as i32),
+        XdrValue::Bool(value) => Ok(value as i32),
+        _ => Err(Error::invalid_schema(
+            "unions must switch on an int, unsigned int, bool or enum".to_string(),
+        )),
+    }
+}
+
+fn wrap<T>((value, read): (T, u64), variant: fn(T) -> XdrValue) -> (XdrValue, u64) {
+    (variant(value), read)
+}
+
+fn write_literal<W: Write + ?Sized>(literal: &str, out: &mut W) -> Result<u64, Error> {
+    out.write_all(literal.as_bytes())?;
+    Ok(literal.len() as u64)
+}
+
+// Arrays read from JSON accept null as empty, as `Vec::read_json` does.
+fn json_array(jval: JsonValue) -> Result<Vec<JsonValue>, Error> {
+    match jval {
+        JsonValue::Array(values) => Ok(values),
+        JsonValue::Null => Ok(Vec::new()),
+        _ => Err(Error::invalid_json()),
+    }
+}
+
+// Whether a union's switch is an `unsigned int`, directly or through
+// typedefs. Other switches hold an `int`.
+fn is_unsigned(schema: &Schema, ty: &Type) -> bool {
+    let mut current = ty;
+    // A longer chain than there are definitions must be a cycle.
+    for _ in 0..=schema.definitions.len() {
+        match current {
+            Type::UnsignedInt => return true,
+            Type::Named(name) => match schema.definition(name) {
+                Some(Definition::Typedef(declaration))
+                    if declaration.kind == DeclarationKind::Single =>
+                {
+                    current = &declaration.ty
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+    }
+    false
+}
+
+fn describe_declaration(
+    schema: &Schema,
+    constants: &Constants,
+    declaration: &Declaration,
+) -> Result<TypeDesc, Error> {
+    let size = |value: &Value| constants.size(value);
+    let max = |value: &Option<Value>| value.as_ref().map(|v| constants.size(v)).transpose();
+    Ok(match (&declaration.ty, &declaration.kind) {
+        (Type::Opaque, DeclarationKind::FixedArray(value)) => TypeDesc::FixedOpaque(size(value)?),
+        (Type::Opaque, DeclarationKind::VarArray(value)) => TypeDesc::VarOpaque(max(value)?),
+        (Type::String, DeclarationKind::VarArray(value)) => TypeDesc::String(max(value)?),
+        (Type::Opaque, _) | (Type::String, _) => {
+            return Err(Error::invalid_schema(format!(
+                "{} must be declared as an array",
+                declaration.name
+            )))
+        }
+        (ty, DeclarationKind::Single) => describe_type(schema, constants, ty)?,
+        (ty, DeclarationKind::FixedArray(value)) => TypeDesc::FixedArray(
+            Box::new(describe_type(schema, constants, ty)?),
+            size(value)?,
+        ),
+        (ty, DeclarationKind::VarArray(value)) => {
+            TypeDesc::VarArray(Box::new(describe_type(schema, constants, ty)?), max(value)?)
+        }
+        (ty, DeclarationKind::Optional) => {
+            TypeDesc::Optional(Box::new(describe_type(schema, constants, ty)?))
+        }
+    })
+}
+
+fn describe_type(schema: &Schema, constants: &Constants, ty: &Type) -> Result<TypeDesc, Error> {
+    Ok(match ty {
+        Type::Int => TypeDesc::Int,
+        Type::UnsignedInt => TypeDesc::UnsignedInt,
+        Type::Hyper => TypeDesc::Hyper,
+        Type::UnsignedHyper => TypeDesc::UnsignedHyper,
+        Type::Float => TypeDesc::Float,
+        Type::Double => TypeDesc::Double,
+        Type::Quadruple => TypeDesc::Quadruple,
+        Type::Bool => TypeDesc::Bool,
+        Type::Void => TypeDesc::Void,
+        Type::Opaque | Type::String => {
+            return Err(Error::invalid_schema(
+                "opaque and string must be declared as arrays".to_string(),
+            ))
+        }
+        Type::Enum(values) => TypeDesc::Enum(constants.enum_values(values)?),
+        Type::Struct(members) => TypeDesc::Struct(
+            members
+                .iter()
+                .map(|m| Ok((m.name.clone(), describe_declaration(schema, constants, m)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        Type::Union(union) => {
+            let range = if is_unsigned(schema, &union.discriminant.ty) {
+                0..=u32::MAX as i64
+            } else {
+                i32::MIN as i64..=i32::MAX as i64
+            };
+            let mut arms = Vec::new();
+            for arm in union.arms.iter() {
+                let ty = describe_declaration(schema, constants, &arm.declaration)?;
+                for case in arm.cases.iter() {
+                    let value = constants.value(case)?;
+                    if !range.contains(&value) {
+                        return Err(Error::invalid_schema(format!(
+                            "case {} is out of range",
+                            value
+                        )));
+                    }
+                    // Unsigned discriminants share the int encoding.
+                    arms.push((
+                        value as u32 as i32,
+                        arm.declaration.name.clone(),
+                        ty.clone(),
+                    ));
+                }
+            }
+            let default = match union.default {
+                Some(ref default) => {
+                    Some(Box::new(describe_declaration(schema, constants, default)?))
+                }
+                None => None,
+            };
+            TypeDesc::Union(UnionDesc {
+                discriminant: Box::new(describe_declaration(
+                    schema,
+                    constants,
+                    &union.discriminant,
+                )?),
+                arms,
+                default,
+            })
+        }
+        Type::Named(name) => TypeDesc::Named(name.clone()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    const SCHEMA: &str = "
+        const N = 2;
+        typedef opaque Hash[4];
+        enum Kind { A = 0, B = 1 };
+        union Body switch (Kind k) {
+            case A: int x;
+            case B: void;
+        };
+        struct Item {
+            Hash id;
+            unsigned hyper amount;
+            string name<10>;
+            opaque blob<>;
+            int pair[N];
+            Item *next;
+            Body body;
+            bool flag;
+            double ratio;
+        };
+        union Open switch (int d) {
+            case 1: int x;
+            default: string s<>;
+        };
+        typedef Loop Cycle;
+        typedef Cycle Loop;
+    ";
+
+    #[derive(XDROut, XDRIn, Debug, PartialEq)]
+    #[xdr(transparent)]
+    struct Hash {
+        #[array(fixed = 4)]
+        t: Vec<u8>,
+    }
+
+    #[derive(XDROut, XDRIn, Debug, PartialEq)]
+    enum Body {
+        #[discriminant(value = "0")]
+        A(i32),
+        #[discriminant(value = "1")]
+        B(()),
+    }
+
+    #[derive(XDROut, XDRIn, Debug, PartialEq)]
+    struct Item {
+        id: Hash,
+        amount: u64,
+        #[array(var = 10)]
+        name: String,
+        blob: Vec<u8>,
+        #[array(fixed = 2)]
+        pair: Vec<i32>,
+        next: Option<Box<Item>>,
+        body: Body,
+        flag: bool,
+        ratio: f64,
+    }
+
+    fn runtime_schema() -> RuntimeSchema {
+        RuntimeSchema::new(&Schema::parse(SCHEMA).unwrap()).unwrap()
+    }
+
+    fn item() -> Item {
+        Item {
+            id: Hash {
+                t: vec![1, 2, 3, 4],
+            },
+            amount: 5,
+            name: "one \"two\"".to_string(),
+            blob: vec![9, 8, 7],
+            pair: vec![-1, 1],
+            next: Some(Box::new(Item {
+                id: Hash { t: vec![0; 4] },
+                amount: 0,
+                name: String::new(),
+                blob: Vec::new(),
+                pair: vec![0, 0],
+                next: None,
+                body: Body::B(()),
+                flag: false,
+                ratio: 0.5,
+            })),
+            body: Body::A(7),
+            flag: true,
+            ratio: 1.0,
+        }
+    }
+
+    fn to_json(schema: &RuntimeSchema, name: &str, bytes: &[u8]) -> Result<String, Error> {
+        let mut out = Vec::new();
+        let ty = TypeDesc::Named(name.to_string());
+        schema.xdr_to_json(&ty, &mut XDRReader::new(bytes), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    fn to_xdr(schema: &RuntimeSchema, name: &str, json: &str) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        let ty = TypeDesc::Named(name.to_string());
+        schema.json_to_xdr(&ty, json::parse(json).unwrap(), &mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_xdr_to_json_matches_derive() {
+        let schema = runtime_schema();
+        let mut bytes = Vec::new();
+        item().write_xdr(&mut bytes).unwrap();
+        let mut expected = Vec::new();
+        item().write_json(&mut expected).unwrap();
+        assert_eq!(
+            String::from_utf8(expected).unwrap(),
+            to_json(&schema, "Item", &bytes).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_to_xdr_matches_derive() {
+        let schema = runtime_schema();
+        let mut json = Vec::new();
+        item().write_json(&mut json).unwrap();
+        let mut expected = Vec::new();
+        item().write_xdr(&mut expected).unwrap();
+        assert_eq!(
+            expected,
+            to_xdr(&schema, "Item", str::from_utf8(&json).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_xdr_value() {
+        let schema = runtime_schema();
+        let body = TypeDesc::Named("Body".to_string());
+        let bytes = vec![0, 0, 0, 0, 255, 255, 255, 254];
+        let (value, read) = schema
+            .read_xdr_from(&body, &mut XDRReader::new(&bytes[..]))
+            .unwrap();
+        assert_eq!(
+            XdrValue::Union(Box::new(XdrValue::Enum(0)), Box::new(XdrValue::Int(-2))),
+            value
+        );
+        assert_eq!(8, read);
+
+        let mut encoded = Vec::new();
+        assert_eq!(8, value.write_xdr(&mut encoded).unwrap());
+        assert_eq!(bytes, encoded);
+        let mut json = Vec::new();
+        value.write_json(&mut json).unwrap();
+        assert_eq!(r#"{"type":0,"data":-2}"#, str::from_utf8(&json).unwrap());
+        assert_eq!(
+            value,
+            schema
+                .read_json(&body, json::parse(r#"{"type":0,"data":-2}"#).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_xdr_value_round_trip() {
+        let schema = runtime_schema();
+        let ty = TypeDesc::Named("Item".to_string());
+        let mut bytes = Vec::new();
+        item().write_xdr(&mut bytes).unwrap();
+        let (value, read) = schema
+            .read_xdr_from(&ty, &mut XDRReader::new(&bytes[..]))
+            .unwrap();
+        assert_eq!(bytes.len() as u64, read);
+        assert_eq!(bytes.len() as u64, value.xdr_size());
+        match value {
+            XdrValue::Struct(ref members) => {
+                assert_eq!("id", members[0].0);
+                assert_eq!(XdrValue::FixedOpaque(vec![1, 2, 3, 4]), members[0].1);
+                assert_eq!(
+                    XdrValue::FixedArray(vec![XdrValue::Int(-1), XdrValue::Int(1)]),
+                    members[4].1
+                );
+            }
+            _ => panic!("expected a struct, got {:?}", value),
+        }
+
+        let mut encoded = Vec::new();
+        value.write_xdr(&mut encoded).unwrap();
+        assert_eq!(bytes, encoded);
+        let mut json = Vec::new();
+        value.write_json(&mut json).unwrap();
+        let parsed = json::parse(str::from_utf8(&json).unwrap()).unwrap();
+        assert_eq!(value, schema.read_json(&ty, parsed).unwrap());
+    }
+
+    #[test]
+    fn test_union_default_arm() {
+        let schema = runtime_schema();
+        let bytes = vec![0, 0, 0, 9, 0, 0, 0, 2, 104, 105, 0, 0];
+        let json = to_json(&schema, "Open", &bytes).unwrap();
+        assert_eq!(r#"{"type":9,"data":"hi"}"#, json);
+        assert_eq!(bytes, to_xdr(&schema, "Open", &json).unwrap());
+    }
+
+    #[test]
+    fn test_struct_member_named_t() {
+        let schema =
+            RuntimeSchema::new(&Schema::parse("struct Wrapper { int t; };").unwrap()).unwrap();
+        let bytes = vec![0, 0, 0, 7];
+        let json = to_json(&schema, "Wrapper", &bytes).unwrap();
+        assert_eq!(r#"{"t":7}"#, json);
+        assert_eq!(bytes, to_xdr(&schema, "Wrapper", &json).unwrap());
+    }
+
+    #[test]
+    fn test_union_case_ranges() {
+        let schema = RuntimeSchema::new(
+            &Schema::parse(
+                "union Signed switch (int d) {
+                     case -2147483648: int x;
+                     case 2147483647: void;
+                 };
+                 typedef unsigned int Code;
+                 union Unsigned switch (Code c) {
+                     case 0: void;
+                     case 4294967295: int x;
+                 };",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let bytes = vec![128, 0, 0, 0, 0, 0, 0, 5];
+        let json = to_json(&schema, "Signed", &bytes).unwrap();
+        assert_eq!(r#"{"type":-2147483648,"data":5}"#, json);
+        assert_eq!(bytes, to_xdr(&schema, "Signed", &json).unwrap());
+        let bytes = vec![255, 255, 255, 255, 0, 0, 0, 5];
+        let json = to_json(&schema, "Unsigned", &bytes).unwrap();
+        assert_eq!(r#"{"type":4294967295,"data":5}"#, json);
+        assert_eq!(bytes, to_xdr(&schema, "Unsigned", &json).unwrap());
+
+        let out_of_range = |source: &str| RuntimeSchema::new(&Schema::parse(source).unwrap());
+        assert_eq!(
+            Err(Error::invalid_schema(
+                "case 2147483648 is out of range".to_string()
+            )),
+            out_of_range("union U switch (int d) { case 2147483648: void; };")
+        );
+        assert_eq!(
+            Err(Error::invalid_schema(
+                "case -2147483649 is out of range".to_string()
+            )),
+            out_of_range("union U switch (int d) { case -2147483649: void; };")
+        );
+        assert_eq!(
+            Err(Error::invalid_schema("case -1 is out of range".to_string())),
+            out_of_range("union U switch (unsigned int d) { case -1: void; };")
+        );
+        assert_eq!(
+            Err(Error::invalid_schema(
+                "case 4294967296 is out of range".to_string()
+            )),
+            out_of_range("union U switch (unsigned int d) { case 4294967296: void; };")
+        );
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let schema = runtime_schema();
+        let err = to_json(&schema, "Body", &[0, 0, 0, 5]).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidEnumValue, err.kind());
+        assert_eq!(Some(0), err.offset());
+
+        let mut bytes = Vec::new();
+        item().write_xdr(&mut bytes).unwrap();
+        let err = to_json(&schema, "Item", &bytes[..bytes.len() - 2]).unwrap_err();
+        assert_eq!(&ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!("ratio", err.path());
+
+        let err = to_xdr(&schema, "Item", r#"{"id": "01020304"}"#).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidJson, err.kind());
+        assert_eq!("amount", err.path());
+
+        let err = to_xdr(&schema, "Item", r#"{"id": "0102"}"#).unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+        assert_eq!("id", err.path());
+        let err = to_xdr(&schema, "Hash", r#""0102030405""#).unwrap_err();
+        assert_eq!(&ErrorKind::BadArraySize, err.kind());
+
+        let err = to_xdr(&schema, "Body", r#"{"type": 0, "data": "x"}"#).unwrap_err();
+        assert_eq!(&ErrorKind::IntegerBadFormat, err.kind());
+
+        assert_eq!(
+            Err(Error::invalid_schema("typedef cycle".to_string())),
+            to_json(&schema, "Loop", &[])
+        );
+        assert_eq!(
+            Err(Error::invalid_schema("unknown type Missing".to_string())),
+            to_json(&schema, "Missing", &[])
+        );
+    }
+}
@@ -0,0 +1,826 @@
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// A parsed XDR language (`.x`) file, as described in RFC 4506 section 6.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    pub definitions: Vec<Definition>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Definition {
+    /// `const NAME = 10;`
+    Const(String, i64),
+    /// `typedef <declaration>;`, named after the declaration.
+    Typedef(Declaration),
+    /// `enum`, `struct` or `union` definitions.
+    Type(String, Type),
+}
+
+/// A type specifier. Enums, structs and unions can be declared inline as
+/// part of another declaration.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Int,
+    UnsignedInt,
+    Hyper,
+    UnsignedHyper,
+    Float,
+    Double,
+    Quadruple,
+    Bool,
+    Opaque,
+    String,
+    Void,
+    /// Enum constants, with their value when it is given explicitly.
+    Enum(Vec<(String, Option<Value>)>),
+    Struct(Vec<Declaration>),
+    Union(Box<Union>),
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    /// Empty for `void`.
+    pub name: String,
+    pub ty: Type,
+    pub kind: DeclarationKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeclarationKind {
+    Single,
+    /// `name[size]`
+    FixedArray(Value),
+    /// `name<max>`, or `name<>` when unbounded.
+    VarArray(Option<Value>),
+    /// `type *name`
+    Optional,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Union {
+    pub discriminant: Declaration,
+    pub arms: Vec<UnionArm>,
+    pub default: Option<Declaration>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnionArm {
+    pub cases: Vec<Value>,
+    pub declaration: Declaration,
+}
+
+/// A literal constant or a reference to a named constant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Constant(i64),
+    Named(String),
+}
+
+/// Values of the named constants of a schema: `const` definitions, enum
+/// constants (including those of inline enums), and `TRUE` and `FALSE`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constants {
+    values: HashMap<String, i64>,
+}
+
+impl Schema {
+    pub fn parse(source: &str) -> Result<Schema, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let mut definitions = Vec::new();
+        while parser.peek().is_some() {
+            definitions.push(parser.definition()?);
+        }
+        Ok(Schema { definitions })
+    }
+
+    pub fn constants(&self) -> Result<Constants, Error> {
+        let mut constants = Constants {
+            values: HashMap::new(),
+        };
+        constants.values.insert("TRUE".to_string(), 1);
+        constants.values.insert("FALSE".to_string(), 0);
+        for definition in self.definitions.iter() {
+            if let Definition::Const(name, value) = definition {
+                constants.values.insert(name.clone(), *value);
+            }
+        }
+        for definition in self.definitions.iter() {
+            match definition {
+                Definition::Const(_, _) => {}
+                Definition::Typedef(declaration) => constants.collect(&declaration.ty)?,
+                Definition::Type(_, ty) => constants.collect(ty)?,
+            }
+        }
+        Ok(constants)
+    }
+
+    /// Looks up the top level definition of a named type or typedef.
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|d| match d {
+            Definition::Const(_, _) => false,
+            Definition::Typedef(declaration) => declaration.name == name,
+            Definition::Type(type_name, _) => type_name == name,
+        })
+    }
+}
+
+impl Constants {
+    fn collect(&mut self, ty: &Type) -> Result<(), Error> {
+        match ty {
+            Type::Enum(constants) => {
+                for (name, value) in self.enum_values(constants)? {
+                    self.values.insert(name, value as i64);
+                }
+            }
+            Type::Struct(members) => {
+                for member in members.iter() {
+                    self.collect(&member.ty)?;
+                }
+            }
+            Type::Union(union) => {
+                self.collect(&union.discriminant.ty)?;
+                for arm in union.arms.iter() {
+                    self.collect(&arm.declaration.ty)?;
+                }
+                if let Some(ref default) = union.default {
+                    self.collect(&default.ty)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn value(&self, value: &Value) -> Result<i64, Error> {
+        match value {
+            Value::Constant(n) => Ok(*n),
+            Value::Named(name) => self
+                .values
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Error::invalid_schema(format!("unknown constant {}", name))),
+        }
+    }
+
+    /// Resolves an array size, which must fit in an unsigned int.
+    pub fn size(&self, value: &Value) -> Result<u32, Error> {
+        let size = self.value(value)?;
+        if size < 0 || size > u32::MAX as i64 {
+            return Err(Error::invalid_schema(format!(
+                "invalid array size {}",
+                size
+            )));
+        }
+        Ok(size as u32)
+    }
+
+    /// Resolves the values of an enum's constants. Constants without a value
+    /// follow on from the previous one.
+    pub fn enum_values(
+        &self,
+        constants: &[(String, Option<Value>)],
+    ) -> Result<Vec<(String, i32)>, Error> {
+        let mut result: Vec<(String, i32)> = Vec::new();
+        let mut next: i64 = 0;
+        for (name, value) in constants.iter() {
+            let value = match value {
+                Some(Value::Named(other)) => match result.iter().find(|c| &c.0 == other) {
+                    Some(c) => c.1 as i64,
+                    None => self.value(&Value::Named(other.clone()))?,
+                },
+                Some(value) => self.value(value)?,
+                None => next,
+            };
+            if value < i32::MIN as i64 || value > i32::MAX as i64 {
+                return Err(Error::invalid_schema(format!(
+                    "enum value {} of {} is out of range",
+                    value, name
+                )));
+            }
+            result.push((name.clone(), value as i32));
+            next = value + 1;
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(i64),
+    Punct(char),
+}
+
+fn schema_error(line: usize, message: String) -> Error {
+    Error::invalid_schema(format!("line {}: {}", line, message))
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let mut line_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            line_start = true;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        // rpcgen passes lines starting with '%' straight through; skip them.
+        if c == '%' && line_start {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        line_start = false;
+        if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let start = line;
+            i += 2;
+            loop {
+                match chars.get(i) {
+                    Some('*') if chars.get(i + 1) == Some(&'/') => break,
+                    Some('\n') => line += 1,
+                    Some(_) => {}
+                    None => return Err(schema_error(start, "unterminated comment".to_string())),
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), line));
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push((Token::Number(parse_number(&text, line)?), line));
+        } else if "{}[]<>();:,=*".contains(c) {
+            tokens.push((Token::Punct(c), line));
+            i += 1;
+        } else {
+            return Err(schema_error(line, format!("unexpected character '{}'", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+// Decimal, hexadecimal (0x) and octal (leading 0) constants. Values up to
+// u64::MAX are accepted for unsigned hyper constants and stored wrapped.
+fn parse_number(text: &str, line: usize) -> Result<i64, Error> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let parsed = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        u64::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse::<u64>()
+    };
+    let value = parsed.map_err(|_| schema_error(line, format!("invalid number '{}'", text)))?;
+    if negative {
+        if value > i64::MAX as u64 + 1 {
+            return Err(schema_error(
+                line,
+                format!("number '{}' out of range", text),
+            ));
+        }
+        Ok((value as i64).wrapping_neg())
+    } else {
+        Ok(value as i64)
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "bool",
+    "case",
+    "const",
+    "default",
+    "double",
+    "quadruple",
+    "enum",
+    "float",
+    "hyper",
+    "int",
+    "opaque",
+    "string",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+];
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.0)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.position) {
+            Some(t) => t.1,
+            None => self.tokens.last().map_or(1, |t| t.1),
+        }
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        let found = match self.peek() {
+            Some(Token::Ident(name)) => format!("'{}'", name),
+            Some(Token::Number(n)) => format!("'{}'", n),
+            Some(Token::Punct(c)) => format!("'{}'", c),
+            None => "end of input".to_string(),
+        };
+        schema_error(
+            self.line(),
+            format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).map(|t| t.0.clone());
+        self.position += 1;
+        token
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(name)) if name == keyword)
+    }
+
+    fn punct(&mut self, c: char) -> Result<(), Error> {
+        if !self.is_punct(c) {
+            return Err(self.error(&format!("'{}'", c)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if !self.is_keyword(keyword) {
+            return Err(self.error(&format!("'{}'", keyword)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("identifier")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.peek() {
+            Some(Token::Number(n)) => {
+                let n = *n;
+                self.position += 1;
+                Ok(Value::Constant(n))
+            }
+            _ => Ok(Value::Named(
+                self.identifier().map_err(|_| self.error("constant"))?,
+            )),
+        }
+    }
+
+    fn definition(&mut self) -> Result<Definition, Error> {
+        let definition = match self.next() {
+            Some(Token::Ident(ref k)) if k == "const" => {
+                let name = self.identifier()?;
+                self.punct('=')?;
+                match self.next() {
+                    Some(Token::Number(n)) => Definition::Const(name, n),
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.error("constant"));
+                    }
+                }
+            }
+            Some(Token::Ident(ref k)) if k == "typedef" => {
+                let declaration = self.declaration()?;
+                if declaration.ty == Type::Void {
+                    return Err(schema_error(self.line(), "cannot typedef void".to_string()));
+                }
+                Definition::Typedef(declaration)
+            }
+            Some(Token::Ident(ref k)) if k == "enum" => {
+                let name = self.identifier()?;
+                Definition::Type(name, self.enum_body()?)
+            }
+            Some(Token::Ident(ref k)) if k == "struct" => {
+                let name = self.identifier()?;
+                Definition::Type(name, self.struct_body()?)
+            }
+            Some(Token::Ident(ref k)) if k == "union" => {
+                let name = self.identifier()?;
+                Definition::Type(name, self.union_body()?)
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error("definition"));
+            }
+        };
+        self.punct(';')?;
+        Ok(definition)
+    }
+
+    fn enum_body(&mut self) -> Result<Type, Error> {
+        self.punct('{')?;
+        let mut constants = Vec::new();
+        loop {
+            let name = self.identifier()?;
+            let value = if self.is_punct('=') {
+                self.punct('=')?;
+                Some(self.value()?)
+            } else {
+                None
+            };
+            constants.push((name, value));
+            if !self.is_punct(',') {
+                break;
+            }
+            self.punct(',')?;
+        }
+        self.punct('}')?;
+        Ok(Type::Enum(constants))
+    }
+
+    fn struct_body(&mut self) -> Result<Type, Error> {
+        self.punct('{')?;
+        let mut members = Vec::new();
+        loop {
+            let declaration = self.declaration()?;
+            if declaration.ty == Type::Void {
+                return Err(schema_error(
+                    self.line(),
+                    "struct members cannot be void".to_string(),
+                ));
+            }
+            members.push(declaration);
+            self.punct(';')?;
+            if self.is_punct('}') {
+                break;
+            }
+        }
+        self.punct('}')?;
+        Ok(Type::Struct(members))
+    }
+
+    fn union_body(&mut self) -> Result<Type, Error> {
+        self.keyword("switch")?;
+        self.punct('(')?;
+        let discriminant = self.declaration()?;
+        self.punct(')')?;
+        self.punct('{')?;
+        let mut arms = Vec::new();
+        while self.is_keyword("case") {
+            let mut cases = Vec::new();
+            while self.is_keyword("case") {
+                self.keyword("case")?;
+                cases.push(self.value()?);
+                self.punct(':')?;
+            }
+            let declaration = self.declaration()?;
+            self.punct(';')?;
+            arms.push(UnionArm { cases, declaration });
+        }
+        let default = if self.is_keyword("default") {
+            self.keyword("default")?;
+            self.punct(':')?;
+            let declaration = self.declaration()?;
+            self.punct(';')?;
+            Some(declaration)
+        } else {
+            None
+        };
+        if arms.is_empty() {
+            return Err(self.error("'case'"));
+        }
+        self.punct('}')?;
+        Ok(Type::Union(Box::new(Union {
+            discriminant,
+            arms,
+            default,
+        })))
+    }
+
+    fn declaration(&mut self) -> Result<Declaration, Error> {
+        if self.is_keyword("void") {
+            self.keyword("void")?;
+            return Ok(Declaration {
+                name: String::new(),
+                ty: Type::Void,
+                kind: DeclarationKind::Single,
+            });
+        }
+        if self.is_keyword("opaque") || self.is_keyword("string") {
+            let ty = match self.next() {
+                Some(Token::Ident(ref k)) if k == "opaque" => Type::Opaque,
+                _ => Type::String,
+            };
+            let name = self.identifier()?;
+            let kind = match self.peek() {
+                Some(Token::Punct('[')) if ty == Type::Opaque => self.array_kind()?,
+                Some(Token::Punct('<')) => self.array_kind()?,
+                _ if ty == Type::Opaque => return Err(self.error("'[' or '<'")),
+                _ => return Err(self.error("'<'")),
+            };
+            return Ok(Declaration { name, ty, kind });
+        }
+        let ty = self.type_spec()?;
+        if self.is_punct('*') {
+            self.punct('*')?;
+            let name = self.identifier()?;
+            return Ok(Declaration {
+                name,
+                ty,
+                kind: DeclarationKind::Optional,
+            });
+        }
+        let name = self.identifier()?;
+        let kind = if self.is_punct('[') || self.is_punct('<') {
+            self.array_kind()?
+        } else {
+            DeclarationKind::Single
+        };
+        Ok(Declaration { name, ty, kind })
+    }
+
+    fn array_kind(&mut self) -> Result<DeclarationKind, Error> {
+        if self.is_punct('[') {
+            self.punct('[')?;
+            let size = self.value()?;
+            self.punct(']')?;
+            return Ok(DeclarationKind::FixedArray(size));
+        }
+        self.punct('<')?;
+        let max = if self.is_punct('>') {
+            None
+        } else {
+            Some(self.value()?)
+        };
+        self.punct('>')?;
+        Ok(DeclarationKind::VarArray(max))
+    }
+
+    fn type_spec(&mut self) -> Result<Type, Error> {
+        let name = match self.peek() {
+            Some(Token::Ident(name)) => name.clone(),
+            _ => return Err(self.error("type")),
+        };
+        let ty = match name.as_str() {
+            "unsigned" => {
+                self.position += 1;
+                if self.is_keyword("int") {
+                    self.position += 1;
+                    return Ok(Type::UnsignedInt);
+                }
+                if self.is_keyword("hyper") {
+                    self.position += 1;
+                    return Ok(Type::UnsignedHyper);
+                }
+                // A bare "unsigned" is an unsigned int.
+                return Ok(Type::UnsignedInt);
+            }
+            "int" => Type::Int,
+            "hyper" => Type::Hyper,
+            "float" => Type::Float,
+            "double" => Type::Double,
+            "quadruple" => Type::Quadruple,
+            "bool" => Type::Bool,
+            "enum" => {
+                self.position += 1;
+                return self.enum_body();
+            }
+            "struct" => {
+                self.position += 1;
+                return self.struct_body();
+            }
+            "union" => {
+                self.position += 1;
+                return self.union_body();
+            }
+            _ => Type::Named(self.identifier().map_err(|_| self.error("type"))?),
+        };
+        if let Type::Named(_) = ty {
+            return Ok(ty);
+        }
+        self.position += 1;
+        Ok(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single(name: &str, ty: Type) -> Declaration {
+        Declaration {
+            name: name.to_string(),
+            ty,
+            kind: DeclarationKind::Single,
+        }
+    }
+
+    #[test]
+    fn test_parse_const_and_typedef() {
+        let schema = Schema::parse(
+            r#"
+            /* sizes */
+            const HASH_SIZE = 0x20;
+            const MAX_NAME = 010; // octal
+            const MIN = -5;
+            typedef opaque Hash[HASH_SIZE];
+            typedef string Name<MAX_NAME>;
+            typedef unsigned hyper Amount;
+            typedef int Ints<>;
+            typedef Hash *MaybeHash;
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                Definition::Const("HASH_SIZE".to_string(), 32),
+                Definition::Const("MAX_NAME".to_string(), 8),
+                Definition::Const("MIN".to_string(), -5),
+                Definition::Typedef(Declaration {
+                    name: "Hash".to_string(),
+                    ty: Type::Opaque,
+                    kind: DeclarationKind::FixedArray(Value::Named("HASH_SIZE".to_string())),
+                }),
+                Definition::Typedef(Declaration {
+                    name: "Name".to_string(),
+                    ty: Type::String,
+                    kind: DeclarationKind::VarArray(Some(Value::Named("MAX_NAME".to_string()))),
+                }),
+                Definition::Typedef(single("Amount", Type::UnsignedHyper)),
+                Definition::Typedef(Declaration {
+                    name: "Ints".to_string(),
+                    ty: Type::Int,
+                    kind: DeclarationKind::VarArray(None),
+                }),
+                Definition::Typedef(Declaration {
+                    name: "MaybeHash".to_string(),
+                    ty: Type::Named("Hash".to_string()),
+                    kind: DeclarationKind::Optional,
+                }),
+            ],
+            schema.definitions
+        );
+    }
+
+    #[test]
+    fn test_parse_enum_struct() {
+        let schema = Schema::parse(
+            "enum Color { RED = 0, GREEN = 2, BLUE };
+             struct Point { int x; quadruple y; enum { ON = 1, OFF = 0 } state; };",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                Definition::Type(
+                    "Color".to_string(),
+                    Type::Enum(vec![
+                        ("RED".to_string(), Some(Value::Constant(0))),
+                        ("GREEN".to_string(), Some(Value::Constant(2))),
+                        ("BLUE".to_string(), None),
+                    ])
+                ),
+                Definition::Type(
+                    "Point".to_string(),
+                    Type::Struct(vec![
+                        single("x", Type::Int),
+                        single("y", Type::Quadruple),
+                        single(
+                            "state",
+                            Type::Enum(vec![
+                                ("ON".to_string(), Some(Value::Constant(1))),
+                                ("OFF".to_string(), Some(Value::Constant(0))),
+                            ])
+                        ),
+                    ])
+                ),
+            ],
+            schema.definitions
+        );
+    }
+
+    #[test]
+    fn test_parse_union() {
+        let schema = Schema::parse(
+            "union Result switch (Color c) {
+                case RED:
+                case GREEN:
+                    int code;
+                case BLUE:
+                    void;
+                default:
+                    string message<>;
+             };",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![Definition::Type(
+                "Result".to_string(),
+                Type::Union(Box::new(Union {
+                    discriminant: single("c", Type::Named("Color".to_string())),
+                    arms: vec![
+                        UnionArm {
+                            cases: vec![
+                                Value::Named("RED".to_string()),
+                                Value::Named("GREEN".to_string())
+                            ],
+                            declaration: single("code", Type::Int),
+                        },
+                        UnionArm {
+                            cases: vec![Value::Named("BLUE".to_string())],
+                            declaration: single("", Type::Void),
+                        },
+                    ],
+                    default: Some(Declaration {
+                        name: "message".to_string(),
+                        ty: Type::String,
+                        kind: DeclarationKind::VarArray(None),
+                    }),
+                }))
+            )],
+            schema.definitions
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let assert_error = |source: &str, message: &str| match Schema::parse(source) {
+            Err(e) => assert_eq!(
+                &crate::error::ErrorKind::InvalidSchema(message.to_string()),
+                e.kind()
+            ),
+            Ok(schema) => panic!("parsed {:?}", schema),
+        };
+        assert_error("struct Foo { int x }", "line 1: expected ';', found '}'");
+        assert_error(
+            "const A = 1;\ntypedef string Name[4];",
+            "line 2: expected '<', found '['",
+        );
+        assert_error(
+            "struct Foo { int int; };",
+            "line 1: expected identifier, found 'int'",
+        );
+        assert_error(
+            "union U switch (int d) { default: void; };",
+            "line 1: expected 'case', found '}'",
+        );
+        assert_error(
+            "typedef int Foo",
+            "line 1: expected ';', found end of input",
+        );
+        assert_error("/* open", "line 1: unterminated comment");
+        assert_error("const A = 0x;", "line 1: invalid number '0x'");
+        assert_error(
+            "program FOO {};",
+            "line 1: expected definition, found 'program'",
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 pub use std::io::Write;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -5,157 +6,259 @@ use std::sync::Arc;
 extern crate base64;
 extern crate hex;
 
-use crate::error::Error;
+use crate::error::{Error, ErrorKind};
+use crate::types::{BoundedString, Quad, VarArray, VarOpaque};
 
 pub trait XDROut {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error>;
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error>;
+    /// The encoded size shared by every value of the type, for types whose
+    /// size does not depend on the value.
+    const XDR_SIZE: Option<usize> = None;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error>;
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error>;
+
+    /// The number of bytes `write_xdr` writes, padding included, worked out
+    /// from the value without encoding it.
+    fn xdr_size(&self) -> u64;
 }
 
-fn pad(written: u64, out: &mut Vec<u8>) -> Result<u64, Error> {
-    match (4 - written % 4) % 4 {
-        0 => Ok(0),
-        1 => match out.write(&[0]) {
-            Ok(1) => Ok(1),
-            _ => Err(Error::invalid_padding()),
-        },
-        2 => match out.write(&[0, 0]) {
-            Ok(2) => Ok(2),
-            _ => Err(Error::invalid_padding()),
-        },
-        3 => match out.write(&[0, 0, 0]) {
-            Ok(3) => Ok(3),
-            _ => Err(Error::invalid_padding()),
-        },
-        _ => Err(Error::invalid_padding()),
+/// Adds up the constant sizes of a struct's members.
+pub const fn sum_xdr_sizes(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut total = 0;
+    let mut index = 0;
+    while index < sizes.len() {
+        match sizes[index] {
+            Some(size) => total += size,
+            None => return None,
+        }
+        index += 1;
+    }
+    Some(total)
+}
+
+/// The constant size of a union whose arms all have the same size.
+pub const fn same_xdr_size(sizes: &[Option<usize>]) -> Option<usize> {
+    if sizes.is_empty() {
+        return None;
+    }
+    let mut index = 1;
+    while index < sizes.len() {
+        match (sizes[0], sizes[index]) {
+            (Some(first), Some(size)) if first == size => {}
+            _ => return None,
+        }
+        index += 1;
+    }
+    sizes[0]
+}
+
+/// The constant size of a fixed array or opaque of `count` elements, each of
+/// `element` bytes.
+pub const fn fixed_xdr_size(element: Option<usize>, count: u32) -> Option<usize> {
+    match element {
+        Some(size) => Some(padded_len(size * count as usize)),
+        None => None,
+    }
+}
+
+const fn padded_len(len: usize) -> usize {
+    len.next_multiple_of(4)
+}
+
+// The size of `len` bytes of opaque data or string, padding included.
+fn padded_size(len: usize) -> u64 {
+    padded_len(len) as u64
+}
+
+// The size of the elements of an array, without its length.
+fn array_xdr_size<T: XDROut>(items: &[T]) -> u64 {
+    match T::XDR_SIZE {
+        Some(size) => (size * items.len()) as u64,
+        None => items.iter().map(T::xdr_size).sum(),
+    }
+}
+
+/// Encodes `val` as XDR into a new buffer.
+pub fn to_xdr_bytes<T: XDROut + ?Sized>(val: &T) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    val.write_xdr(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Encodes `val` as XDR into the start of `buffer` without allocating,
+/// returning the number of bytes written. A buffer shorter than the encoding
+/// fails with `BufferTooSmall` before anything is written. A value that fails
+/// to encode, such as an array over its bound, may leave the encoding up to
+/// the failure in the buffer.
+pub fn to_xdr_slice<T: XDROut + ?Sized>(val: &T, buffer: &mut [u8]) -> Result<usize, Error> {
+    if val.xdr_size() > buffer.len() as u64 {
+        return Err(Error::buffer_too_small());
+    }
+    let mut out = buffer;
+    match val.write_xdr(&mut out) {
+        Ok(written) => Ok(written as usize),
+        // Only reached if `xdr_size` undercounts.
+        Err(ref e) if e.kind() == &ErrorKind::IOError(std::io::ErrorKind::WriteZero) => {
+            Err(Error::buffer_too_small())
+        }
+        Err(e) => Err(e),
     }
 }
 
+/// Encodes `val` as a JSON string.
+pub fn to_json_string<T: XDROut + ?Sized>(val: &T) -> Result<String, Error> {
+    let mut buffer = Vec::new();
+    val.write_json(&mut buffer)?;
+    String::from_utf8(buffer).map_err(|e| Error::from(e.utf8_error()))
+}
+
+fn write_bytes<W: Write + ?Sized>(bytes: &[u8], out: &mut W) -> Result<u64, Error> {
+    out.write_all(bytes)?;
+    Ok(bytes.len() as u64)
+}
+
+fn pad<W: Write + ?Sized>(written: u64, out: &mut W) -> Result<u64, Error> {
+    let padding = ((4 - written % 4) % 4) as usize;
+    write_bytes(&[0; 3][..padding], out)
+}
+
 impl XDROut for bool {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+    const XDR_SIZE: Option<usize> = Some(4);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
         let to_write: u32 = if *self { 1 } else { 0 };
-        match out.write(&to_write.to_be_bytes()) {
-            Ok(4) => Ok(4),
-            _ => Err(Error::bool_bad_format()),
-        }
+        write_bytes(&to_write.to_be_bytes(), out)
     }
 
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
         let to_write = if !self { "false" } else { "true" };
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::bool_bad_format()),
-        }
+        write_bytes(to_write.as_bytes(), out)
+    }
+
+    fn xdr_size(&self) -> u64 {
+        4
     }
 }
 
 impl XDROut for i32 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_be_bytes()) {
-            Ok(4) => Ok(4),
-            _ => Err(Error::integer_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(4);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let to_write = self.to_string();
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
-        }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(self.to_string().as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4
     }
 }
 
 impl XDROut for u32 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_be_bytes()) {
-            Ok(4) => Ok(4),
-            _ => Err(Error::unsigned_integer_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(4);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let to_write = self.to_string();
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
-        }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(self.to_string().as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4
     }
 }
 
 impl XDROut for i64 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_be_bytes()) {
-            Ok(8) => Ok(8),
-            _ => Err(Error::hyper_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(8);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let to_write = format!("\"{}\"", self.to_string());
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
-        }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(format!("\"{}\"", self).as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        8
     }
 }
 
 impl XDROut for u64 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_be_bytes()) {
-            Ok(8) => Ok(8),
-            _ => Err(Error::unsigned_hyper_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(8);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let to_write = format!("\"{}\"", self.to_string());
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
-        }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(format!("\"{}\"", self).as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        8
     }
 }
 
 impl XDROut for f32 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_bits().to_be_bytes()) {
-            Ok(4) => Ok(4),
-            _ => Err(Error::float_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(4);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_bits().to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
         let mut to_write = self.to_string();
         if !to_write.contains('.') {
             to_write.push_str(".0")
         }
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
-        }
+        write_bytes(to_write.as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4
     }
 }
 
 impl XDROut for f64 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match out.write(&self.to_bits().to_be_bytes()) {
-            Ok(8) => Ok(8),
-            _ => Err(Error::double_bad_format()),
-        }
+    const XDR_SIZE: Option<usize> = Some(8);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_bits().to_be_bytes(), out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
         let mut to_write = self.to_string();
         if !to_write.contains('.') {
             to_write.push_str(".0")
         }
-        match out.write(to_write.as_bytes()) {
-            Ok(len) => Ok(len as u64),
-            _ => Err(Error::integer_bad_format()),
+        write_bytes(to_write.as_bytes(), out)
+    }
+    fn xdr_size(&self) -> u64 {
+        8
+    }
+}
+
+// Quads that are exactly an f64 are written as JSON numbers, anything else
+// as a hex string of the big-endian bits.
+impl XDROut for Quad {
+    const XDR_SIZE: Option<usize> = Some(16);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(&self.to_bits().to_be_bytes(), out)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        match self.to_f64() {
+            Some(val) if val.is_finite() => val.write_json(out),
+            _ => write_bytes(
+                format!("\"{}\"", hex::encode(self.to_bits().to_be_bytes())).as_bytes(),
+                out,
+            ),
         }
     }
+    fn xdr_size(&self) -> u64 {
+        16
+    }
 }
 
 impl<T> XDROut for Vec<T>
 where
     T: XDROut,
 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
         let mut written: u64 = 0;
         let size: u32 = self.len() as u32;
         written += size.write_xdr(out)?;
@@ -164,79 +267,186 @@ where
         }
         Ok(written)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        if self.is_empty() {
-            return Ok(out.write(b"[]").unwrap() as u64);
-        }
-
-        let mut written = 0;
-        written += out.write(b"[").unwrap() as u64;
-        written += self[0].write_json(out)?;
-        if self.len() == 1 {
-            written += out.write(b"]").unwrap() as u64;
-            return Ok(written);
-        }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_json_array(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + array_xdr_size(self)
+    }
+}
 
-        for item in &self[1..] {
-            written += out.write(b",").unwrap() as u64;
-            written += item.write_json(out)?;
+fn write_json_array<T: XDROut, W: Write + ?Sized>(items: &[T], out: &mut W) -> Result<u64, Error> {
+    let mut written = 0;
+    written += write_bytes(b"[", out)?;
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            written += write_bytes(b",", out)?;
         }
-        written += out.write(b"]").unwrap() as u64;
-        Ok(written)
+        written += item.write_json(out)?;
     }
+    written += write_bytes(b"]", out)?;
+    Ok(written)
 }
 
 impl XDROut for Vec<u8> {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let mut written: u64 = self.len() as u64;
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        self.as_slice().write_xdr(out)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_base64_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
+}
+
+impl XDROut for [u8] {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        let mut written: u64 = 0;
         let size: u32 = self.len() as u32;
         written += size.write_xdr(out)?;
-        out.extend_from_slice(&self);
+        written += write_bytes(self, out)?;
         written += pad(written, out)?;
         Ok(written)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let b64 = base64::encode(&self);
-        let mut written = 0;
-        written += out.write(b"\"").unwrap() as u64;
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_base64_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
+}
 
-        match out.write(b64.as_bytes()) {
-            Ok(len) => {
-                written += len as u64;
-            }
-            _ => {
-                return Err(Error::integer_bad_format());
-            }
-        };
-        written += out.write(b"\"").unwrap() as u64;
+fn write_base64_json<W: Write + ?Sized>(bytes: &[u8], out: &mut W) -> Result<u64, Error> {
+    let b64 = base64::encode(bytes);
+    let mut written = 0;
+    written += write_bytes(b"\"", out)?;
+    written += write_bytes(b64.as_bytes(), out)?;
+    written += write_bytes(b"\"", out)?;
+    Ok(written)
+}
+
+impl<T, const N: usize> XDROut for [T; N]
+where
+    T: XDROut,
+{
+    const XDR_SIZE: Option<usize> = fixed_xdr_size(T::XDR_SIZE, N as u32);
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_fixed_array(self, N as u32, out)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_json_array(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        array_xdr_size(self)
+    }
+}
+
+impl<const N: usize> XDROut for [u8; N] {
+    const XDR_SIZE: Option<usize> = Some(padded_len(N));
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque(self, N as u32, out)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque_json(self, N as u32, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        padded_size(N)
+    }
+}
+
+impl<const MAX: u32> XDROut for VarOpaque<MAX> {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        let mut written = (self.len() as u32).write_xdr(out)?;
+        written += write_bytes(self, out)?;
+        written += pad(written, out)?;
+        Ok(written)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_base64_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
+}
+
+impl<T, const MAX: u32> XDROut for VarArray<T, MAX>
+where
+    T: XDROut,
+{
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        let mut written = (self.len() as u32).write_xdr(out)?;
+        written += write_fixed_array(self, self.len() as u32, out)?;
         Ok(written)
     }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_json_array(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + array_xdr_size(self)
+    }
+}
+
+impl<const MAX: u32> XDROut for BoundedString<MAX> {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        let mut written = (self.len() as u32).write_xdr(out)?;
+        written += write_bytes(self.as_bytes(), out)?;
+        written += pad(written, out)?;
+        Ok(written)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_str_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
 }
 
 impl<T> XDROut for Option<T>
 where
     T: XDROut,
 {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match self {
-            None => 0u32.write_xdr(out),
-            Some(value) => {
-                let mut written = 1u32.write_xdr(out)?;
-                written += value.write_xdr(out)?;
-                Ok(written)
-            }
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_optional_xdr(self, out, |value, out| value.write_xdr(out))
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_optional_json(self, out, |value, out| value.write_json(out))
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + self.as_ref().map_or(0, T::xdr_size)
+    }
+}
+
+fn write_optional_xdr<T, W, F>(val: &Option<T>, out: &mut W, write: F) -> Result<u64, Error>
+where
+    W: Write + ?Sized,
+    F: FnOnce(&T, &mut W) -> Result<u64, Error>,
+{
+    match val {
+        None => 0u32.write_xdr(out),
+        Some(value) => {
+            let mut written = 1u32.write_xdr(out)?;
+            written += write(value, out)?;
+            Ok(written)
         }
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        match self {
-            None => Ok(out.write(b"[]").unwrap() as u64),
-            Some(value) => {
-                let mut written = 0;
-                written += out.write(b"[").unwrap() as u64;
-                written += value.write_json(out)?;
-                written += out.write(b"]").unwrap() as u64;
-                Ok(written)
-            }
+}
+
+fn write_optional_json<T, W, F>(val: &Option<T>, out: &mut W, write: F) -> Result<u64, Error>
+where
+    W: Write + ?Sized,
+    F: FnOnce(&T, &mut W) -> Result<u64, Error>,
+{
+    match val {
+        None => write_bytes(b"[]", out),
+        Some(value) => {
+            let mut written = 0;
+            written += write_bytes(b"[", out)?;
+            written += write(value, out)?;
+            written += write_bytes(b"]", out)?;
+            Ok(written)
         }
     }
 }
@@ -245,12 +455,18 @@ impl<T> XDROut for Arc<T>
 where
     T: XDROut,
 {
-    fn write_xdr(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_xdr(&mut out)
+    const XDR_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_xdr(out)
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_json(out)
     }
 
-    fn write_json(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_json(&mut out)
+    fn xdr_size(&self) -> u64 {
+        (**self).xdr_size()
     }
 }
 
@@ -258,12 +474,18 @@ impl<T> XDROut for Rc<T>
 where
     T: XDROut,
 {
-    fn write_xdr(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_xdr(&mut out)
+    const XDR_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_xdr(out)
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_json(out)
     }
 
-    fn write_json(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_json(&mut out)
+    fn xdr_size(&self) -> u64 {
+        (**self).xdr_size()
     }
 }
 
@@ -271,21 +493,70 @@ impl<T> XDROut for Box<T>
 where
     T: XDROut,
 {
-    fn write_xdr(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_xdr(&mut out)
+    const XDR_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_xdr(out)
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_json(out)
+    }
+
+    fn xdr_size(&self) -> u64 {
+        (**self).xdr_size()
+    }
+}
+
+impl<T> XDROut for &T
+where
+    T: XDROut + ?Sized,
+{
+    const XDR_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_xdr(out)
+    }
+
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_json(out)
+    }
+
+    fn xdr_size(&self) -> u64 {
+        (**self).xdr_size()
+    }
+}
+
+impl<T> XDROut for Cow<'_, T>
+where
+    T: XDROut + ToOwned + ?Sized,
+{
+    const XDR_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_xdr(out)
     }
 
-    fn write_json(&self, mut out: &mut Vec<u8>) -> Result<u64, Error> {
-        (**self).write_json(&mut out)
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        (**self).write_json(out)
+    }
+
+    fn xdr_size(&self) -> u64 {
+        (**self).xdr_size()
     }
 }
 
 impl XDROut for () {
-    fn write_xdr(&self, _out: &mut Vec<u8>) -> Result<u64, Error> {
+    const XDR_SIZE: Option<usize> = Some(0);
+
+    fn write_xdr<W: Write + ?Sized>(&self, _out: &mut W) -> Result<u64, Error> {
         Ok(0)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        Ok(out.write(b"\"\"").unwrap() as u64)
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_bytes(b"\"\"", out)
+    }
+    fn xdr_size(&self) -> u64 {
+        0
     }
 }
 
@@ -322,49 +593,307 @@ static ESCAPE: [u8; 256] = [
 ];
 
 impl XDROut for String {
-    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        self.as_bytes().to_vec().write_xdr(out)
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        self.as_str().write_xdr(out)
     }
-    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
-        let bytes = self.as_bytes();
-        let mut written = 0;
-        let mut start = 0;
-
-        written += out.write(b"\"").unwrap();
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_str_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
+}
 
-        for (i, &byte) in bytes.iter().enumerate() {
-            let escape = ESCAPE[byte as usize];
-            if escape == 0 {
-                continue;
-            }
-            if start < i {
-                written += out.write(&bytes[start..i]).unwrap();
-            }
+impl XDROut for str {
+    fn write_xdr<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        self.as_bytes().write_xdr(out)
+    }
+    fn write_json<W: Write + ?Sized>(&self, out: &mut W) -> Result<u64, Error> {
+        write_str_json(self, out)
+    }
+    fn xdr_size(&self) -> u64 {
+        4 + padded_size(self.len())
+    }
+}
 
-            let to_write = match escape {
-                QU => b"\\\"",
-                BS => b"\\\\",
-                BB => b"\\b",
-                FF => b"\\f",
-                NN => b"\\n",
-                RR => b"\\r",
-                TT => b"\\t",
-                _ => panic!("Invalid character"),
-            };
+fn write_str_json<W: Write + ?Sized>(val: &str, out: &mut W) -> Result<u64, Error> {
+    let bytes = val.as_bytes();
+    let mut written = 0;
+    let mut start = 0;
 
-            written += out.write(to_write).unwrap();
+    written += write_bytes(b"\"", out)?;
 
-            start = i + 1
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape = ESCAPE[byte as usize];
+        if escape == 0 {
+            continue;
         }
-        if start != bytes.len() {
-            written += out.write(&bytes[start..]).unwrap();
+        if start < i {
+            written += write_bytes(&bytes[start..i], out)?;
         }
-        written += out.write(b"\"").unwrap();
-        Ok(written as u64)
+
+        let to_write = match escape {
+            QU => b"\\\"",
+            BS => b"\\\\",
+            BB => b"\\b",
+            FF => b"\\f",
+            NN => b"\\n",
+            RR => b"\\r",
+            TT => b"\\t",
+            _ => panic!("Invalid character"),
+        };
+
+        written += write_bytes(to_write, out)?;
+
+        start = i + 1
+    }
+    if start != bytes.len() {
+        written += write_bytes(&bytes[start..], out)?;
+    }
+    written += write_bytes(b"\"", out)?;
+    Ok(written)
+}
+
+/// Encodes a field marked `#[array(fixed = N)]`, which must hold exactly
+/// `size` elements or bytes.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(fixed = N)]` cannot be used with `{Self}`",
+    label = "fixed arrays must be a `Vec` or `&[u8]`, or an `Option` of one"
+)]
+pub trait XDRFixedOut {
+    /// The constant size of each element, from which the size of a fixed
+    /// array of them follows.
+    const XDR_ELEMENT_SIZE: Option<usize> = None;
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error>;
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error>;
+    /// The number of bytes `write_fixed_xdr` writes.
+    fn fixed_xdr_size(&self) -> u64;
+}
+
+/// Encodes a field marked `#[array(var = N)]`, which may hold at most
+/// `max` elements or bytes.
+#[diagnostic::on_unimplemented(
+    message = "`#[array(var = N)]` cannot be used with `{Self}`",
+    label = "var arrays must be a `Vec`, `String`, `&[u8]` or `&str`, or an `Option` of one"
+)]
+pub trait XDRVarOut {
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error>;
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error>;
+    /// The number of bytes `write_var_xdr` writes.
+    fn var_xdr_size(&self) -> u64;
+}
+
+impl<T> XDRFixedOut for Vec<T>
+where
+    T: XDROut,
+{
+    const XDR_ELEMENT_SIZE: Option<usize> = T::XDR_SIZE;
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_array(self, size, out)
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_array_json(self, size, out)
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        array_xdr_size(self)
+    }
+}
+
+impl XDRFixedOut for Vec<u8> {
+    const XDR_ELEMENT_SIZE: Option<usize> = Some(1);
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque(self, size, out)
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque_json(self, size, out)
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        padded_size(self.len())
+    }
+}
+
+impl<T> XDRVarOut for Vec<T>
+where
+    T: XDROut,
+{
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_array(self, max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_array_json(self, max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        self.xdr_size()
+    }
+}
+
+impl XDRVarOut for Vec<u8> {
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_opaque(self, max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_opaque_json(self, max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        self.xdr_size()
+    }
+}
+
+// An optional array is bounded when it is present.
+impl<T> XDRFixedOut for Option<T>
+where
+    T: XDRFixedOut,
+{
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_optional_xdr(self, out, |value, out| value.write_fixed_xdr(size, out))
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_optional_json(self, out, |value, out| value.write_fixed_json(size, out))
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        4 + self.as_ref().map_or(0, T::fixed_xdr_size)
+    }
+}
+
+impl<T> XDRVarOut for Option<T>
+where
+    T: XDRVarOut,
+{
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_optional_xdr(self, out, |value, out| value.write_var_xdr(max, out))
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_optional_json(self, out, |value, out| value.write_var_json(max, out))
+    }
+    fn var_xdr_size(&self) -> u64 {
+        4 + self.as_ref().map_or(0, T::var_xdr_size)
+    }
+}
+
+impl XDRVarOut for String {
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_string(self, max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_string_json(self, max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        self.xdr_size()
     }
 }
 
-pub fn write_fixed_array<T: XDROut>(val: &[T], size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+impl XDRFixedOut for [u8] {
+    const XDR_ELEMENT_SIZE: Option<usize> = Some(1);
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque(self, size, out)
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        write_fixed_opaque_json(self, size, out)
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        padded_size(self.len())
+    }
+}
+
+impl XDRVarOut for [u8] {
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_opaque(self, max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_opaque_json(self, max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        self.xdr_size()
+    }
+}
+
+impl XDRVarOut for str {
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_string(self, max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        write_var_string_json(self, max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        self.xdr_size()
+    }
+}
+
+// Borrowed arrays, as decoded by `XDRInBorrowed`, are written like the
+// values they point to.
+impl<T> XDRFixedOut for &T
+where
+    T: XDRFixedOut + ?Sized,
+{
+    const XDR_ELEMENT_SIZE: Option<usize> = T::XDR_ELEMENT_SIZE;
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_fixed_xdr(size, out)
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_fixed_json(size, out)
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        (**self).fixed_xdr_size()
+    }
+}
+
+impl<T> XDRVarOut for &T
+where
+    T: XDRVarOut + ?Sized,
+{
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_var_xdr(max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_var_json(max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        (**self).var_xdr_size()
+    }
+}
+
+impl<T> XDRFixedOut for Cow<'_, T>
+where
+    T: XDRFixedOut + ToOwned + ?Sized,
+{
+    const XDR_ELEMENT_SIZE: Option<usize> = T::XDR_ELEMENT_SIZE;
+
+    fn write_fixed_xdr<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_fixed_xdr(size, out)
+    }
+    fn write_fixed_json<W: Write + ?Sized>(&self, size: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_fixed_json(size, out)
+    }
+    fn fixed_xdr_size(&self) -> u64 {
+        (**self).fixed_xdr_size()
+    }
+}
+
+impl<T> XDRVarOut for Cow<'_, T>
+where
+    T: XDRVarOut + ToOwned + ?Sized,
+{
+    fn write_var_xdr<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_var_xdr(max, out)
+    }
+    fn write_var_json<W: Write + ?Sized>(&self, max: u32, out: &mut W) -> Result<u64, Error> {
+        (**self).write_var_json(max, out)
+    }
+    fn var_xdr_size(&self) -> u64 {
+        (**self).var_xdr_size()
+    }
+}
+
+pub fn write_fixed_array<T: XDROut, W: Write + ?Sized>(
+    val: &[T],
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 != size {
         return Err(Error::fixed_array_wrong_size());
     }
@@ -375,10 +904,10 @@ pub fn write_fixed_array<T: XDROut>(val: &[T], size: u32, out: &mut Vec<u8>) ->
     Ok(written)
 }
 
-pub fn write_fixed_array_json<T: XDROut>(
+pub fn write_fixed_array_json<T: XDROut, W: Write + ?Sized>(
     val: &Vec<T>,
     size: u32,
-    out: &mut Vec<u8>,
+    out: &mut W,
 ) -> Result<u64, Error> {
     if val.len() as u32 != size {
         return Err(Error::fixed_array_wrong_size());
@@ -386,17 +915,24 @@ pub fn write_fixed_array_json<T: XDROut>(
     val.write_json(out)
 }
 
-pub fn write_fixed_opaque(val: &Vec<u8>, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_fixed_opaque<W: Write + ?Sized>(
+    val: &[u8],
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 != size {
         return Err(Error::fixed_array_wrong_size());
     }
-    out.extend(val);
-    let mut written = val.len() as u64;
+    let mut written = write_bytes(val, out)?;
     written += pad(written, out)?;
     Ok(written)
 }
 
-pub fn write_fixed_opaque_json(val: &Vec<u8>, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_fixed_opaque_json<W: Write + ?Sized>(
+    val: &[u8],
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     let len = val.len() as u32;
     if len != size {
         return Err(Error::fixed_array_wrong_size());
@@ -405,39 +941,40 @@ pub fn write_fixed_opaque_json(val: &Vec<u8>, size: u32, out: &mut Vec<u8>) -> R
     if len <= 64 {
         let hex = hex::encode(val);
         let mut written = 0;
-        written += out.write(b"\"").unwrap() as u64;
-        match out.write(hex.as_bytes()) {
-            Ok(len) => {
-                written += len as u64;
-            }
-            _ => {
-                return Err(Error::integer_bad_format());
-            }
-        };
-        written += out.write(b"\"").unwrap() as u64;
+        written += write_bytes(b"\"", out)?;
+        written += write_bytes(hex.as_bytes(), out)?;
+        written += write_bytes(b"\"", out)?;
         return Ok(written);
     }
-    val.write_json(out)
+    write_base64_json(val, out)
 }
 
-pub fn write_var_opaque(val: &Vec<u8>, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_var_opaque<W: Write + ?Sized>(
+    val: &[u8],
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 > size {
         return Err(Error::bad_array_size());
     }
     val.write_xdr(out)
 }
 
-pub fn write_var_opaque_json(val: &Vec<u8>, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_var_opaque_json<W: Write + ?Sized>(
+    val: &[u8],
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 > size {
         return Err(Error::bad_array_size());
     }
     val.write_json(out)
 }
 
-pub fn write_var_array<T: XDROut>(
+pub fn write_var_array<T: XDROut, W: Write + ?Sized>(
     val: &Vec<T>,
     size: u32,
-    out: &mut Vec<u8>,
+    out: &mut W,
 ) -> Result<u64, Error> {
     if val.len() as u32 > size {
         return Err(Error::var_array_wrong_size());
@@ -445,10 +982,10 @@ pub fn write_var_array<T: XDROut>(
     val.write_xdr(out)
 }
 
-pub fn write_var_array_json<T: XDROut>(
+pub fn write_var_array_json<T: XDROut, W: Write + ?Sized>(
     val: &Vec<T>,
     size: u32,
-    out: &mut Vec<u8>,
+    out: &mut W,
 ) -> Result<u64, Error> {
     if val.len() as u32 > size {
         return Err(Error::var_array_wrong_size());
@@ -456,14 +993,22 @@ pub fn write_var_array_json<T: XDROut>(
     val.write_json(out)
 }
 
-pub fn write_var_string(val: String, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_var_string<W: Write + ?Sized>(
+    val: &str,
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 > size && size != 0 {
         return Err(Error::var_array_wrong_size());
     }
     val.write_xdr(out)
 }
 
-pub fn write_var_string_json(val: String, size: u32, out: &mut Vec<u8>) -> Result<u64, Error> {
+pub fn write_var_string_json<W: Write + ?Sized>(
+    val: &str,
+    size: u32,
+    out: &mut W,
+) -> Result<u64, Error> {
     if val.len() as u32 > size && size != 0 {
         return Err(Error::var_array_wrong_size());
     }
@@ -540,7 +1085,7 @@ mod tests {
 
     #[test]
     fn test_uint() {
-        let to_ser: u32 = std::u32::MAX;
+        let to_ser: u32 = u32::MAX;
         let expected: Vec<u8> = vec![255, 255, 255, 255];
         let mut actual: Vec<u8> = Vec::new();
         to_ser.write_xdr(&mut actual).unwrap();
@@ -576,7 +1121,7 @@ mod tests {
 
     #[test]
     fn test_uhyper() {
-        let to_ser: u64 = std::u64::MAX;
+        let to_ser: u64 = u64::MAX;
         let expected: Vec<u8> = vec![255, 255, 255, 255, 255, 255, 255, 255];
         let mut actual: Vec<u8> = Vec::new();
         to_ser.write_xdr(&mut actual).unwrap();
@@ -602,8 +1147,26 @@ mod tests {
     }
 
     #[test]
-    fn test_float_json() {
-        let to_ser: f32 = 1.0;
+    fn test_float_json() {
+        let to_ser: f32 = 1.0;
+        let expected: Vec<u8> = b"1.0".to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+    }
+
+    #[test]
+    fn test_double() {
+        let to_ser: f64 = 1.0;
+        let expected: Vec<u8> = vec![0x3f, 0xf0, 0, 0, 0, 0, 0, 0];
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_xdr(&mut actual).unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_double_json() {
+        let to_ser: f64 = 1.0;
         let expected: Vec<u8> = b"1.0".to_vec();
         let mut actual: Vec<u8> = Vec::new();
         to_ser.write_json(&mut actual).unwrap();
@@ -611,18 +1174,24 @@ mod tests {
     }
 
     #[test]
-    fn test_double() {
-        let to_ser: f64 = 1.0;
-        let expected: Vec<u8> = vec![0x3f, 0xf0, 0, 0, 0, 0, 0, 0];
+    fn test_quad() {
+        let to_ser = Quad::from(1.0);
+        let expected: Vec<u8> = vec![0x3f, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
         let mut actual: Vec<u8> = Vec::new();
         to_ser.write_xdr(&mut actual).unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
-    fn test_double_json() {
-        let to_ser: f64 = 1.0;
-        let expected: Vec<u8> = b"1.0".to_vec();
+    fn test_quad_json() {
+        let to_ser = Quad::from(-2.5);
+        let expected: Vec<u8> = b"-2.5".to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+
+        let to_ser = Quad::from_bits(0x3fff_0000_0000_0000_0000_0000_0000_0001);
+        let expected: Vec<u8> = br#""3fff0000000000000000000000000001""#.to_vec();
         let mut actual: Vec<u8> = Vec::new();
         to_ser.write_json(&mut actual).unwrap();
         assert_json!(expected, actual);
@@ -815,6 +1384,23 @@ mod tests {
         assert_json!(expected, actual);
     }
 
+    #[derive(XDROut)]
+    struct TestStructOpaque {
+        one: Vec<u8>,
+    }
+
+    #[test]
+    fn test_struct_unbounded_opaque() {
+        let to_ser = TestStructOpaque { one: vec![1, 2] };
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 2, 1, 2, 0, 0], actual);
+        let expected: Vec<u8> = r#"{"one":"AQI="}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+    }
+
     #[derive(Default, XDROut)]
     struct TestFixed {
         #[array(fixed = 3)]
@@ -822,6 +1408,7 @@ mod tests {
     }
 
     #[derive(Default, XDROut)]
+    #[xdr(transparent)]
     struct TestFixedSingle {
         #[array(fixed = 32)]
         pub t: Vec<u8>,
@@ -980,6 +1567,63 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_struct_buf_writer() {
+        let to_ser = TestStruct { one: 1.0, two: 2 };
+        let expected: Vec<u8> = vec![0x3f, 0x80, 0, 0, 0, 0, 0, 2];
+        let mut writer = std::io::BufWriter::new(Vec::new());
+        let written = to_ser.write_xdr(&mut writer).unwrap();
+        assert_eq!(8, written);
+        assert_eq!(expected, writer.into_inner().unwrap());
+    }
+
+    #[test]
+    fn test_to_bytes_and_string() {
+        let to_ser = TestStruct { one: 1.0, two: 2 };
+        assert_eq!(
+            Ok(vec![0x3f, 0x80, 0, 0, 0, 0, 0, 2]),
+            to_xdr_bytes(&to_ser)
+        );
+        assert_eq!(
+            Ok(r#"{"one":1.0,"two":2}"#.to_string()),
+            to_json_string(&to_ser)
+        );
+    }
+
+    #[test]
+    fn test_struct_json_dyn_writer() {
+        let to_ser = TestStruct { one: 1.0, two: 2 };
+        let expected: Vec<u8> = r#"{"one":1.0,"two":2}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        let out: &mut dyn Write = &mut actual;
+        let written = to_ser.write_json(out).unwrap();
+        assert_json!(expected, actual);
+        assert_eq!(expected.len() as u64, written);
+    }
+
+    #[test]
+    fn test_var_opaque_cursor() {
+        let to_ser: Vec<u8> = vec![3, 3, 3, 4, 1];
+        let expected: [u8; 12] = [0, 0, 0, 5, 3, 3, 3, 4, 1, 0, 0, 0];
+        let mut actual = std::io::Cursor::new([0xff; 12]);
+        let written = to_ser.write_xdr(&mut actual).unwrap();
+        assert_eq!(12, written);
+        assert_eq!(expected, actual.into_inner());
+    }
+
+    #[test]
+    fn test_writer_error() {
+        let to_ser = TestStruct { one: 1.0, two: 2 };
+        let mut buffer = [0; 6];
+        let result = to_ser.write_xdr(&mut std::io::Cursor::new(&mut buffer[..]));
+        assert_eq!(
+            Err(Error::from(std::io::Error::from(
+                std::io::ErrorKind::WriteZero
+            ))),
+            result
+        );
+    }
+
     test_wrap!(test_box, test_box_json, Box);
     test_wrap!(test_rc, test_rc_json, Rc);
     test_wrap!(test_arc, test_arc_json, Arc);
@@ -1027,18 +1671,104 @@ mod tests {
         assert_json!(expected_two, actual_two);
     }
 
+    #[derive(XDROut, Debug)]
+    enum TestNegativeEnum {
+        Minus = -1,
+    }
+
+    #[derive(XDROut, Debug)]
+    enum TestUnionGeneric {
+        List(Vec<u32>),
+    }
+
+    #[derive(XDROut, Debug)]
+    struct TestKeyword {
+        r#type: u32,
+    }
+
+    #[test]
+    fn test_generated_shapes() {
+        let mut actual: Vec<u8> = Vec::new();
+        TestNegativeEnum::Minus.write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![255, 255, 255, 255], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionGeneric::List(vec![7])
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 7], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestKeyword { r#type: 3 }.write_json(&mut actual).unwrap();
+        let expected: Vec<u8> = br#"{"type":3}"#.to_vec();
+        assert_json!(expected, actual);
+    }
+
+    #[derive(XDROut)]
+    struct TestEnvelope<'a, T, U>
+    where
+        U: Clone,
+    {
+        body: &'a T,
+        items: Vec<U>,
+    }
+
     #[derive(XDROut)]
-    enum TestEnumBad {
-        Value,
+    enum TestGenericUnion<T> {
+        #[discriminant(value = "1")]
+        Value(T),
+        #[discriminant(value = "2")]
+        Nothing(()),
+    }
+
+    #[test]
+    fn test_generics() {
+        let body = TestGenericUnion::Value(5u32);
+        let envelope = TestEnvelope {
+            body: &body,
+            items: vec![true],
+        };
+        let mut actual: Vec<u8> = Vec::new();
+        envelope.write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 5, 0, 0, 0, 1, 0, 0, 0, 1], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        envelope.write_json(&mut actual).unwrap();
+        let expected: Vec<u8> = br#"{"body":{"type":1,"data":5},"items":[true]}"#.to_vec();
+        assert_json!(expected, actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestGenericUnion::<u32>::Nothing(())
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![0, 0, 0, 2], actual);
     }
 
+    #[derive(XDROut)]
+    struct TestNewtype(#[array(fixed = 4)] Vec<u8>);
+
+    #[derive(XDROut)]
+    struct TestTuple(u32, TestNewtype, String);
+
     #[test]
-    fn test_enum_bad() {
-        let mut buffer: Vec<u8> = Vec::new();
-        let result = TestEnumBad::Value.write_xdr(&mut buffer);
-        assert_eq!(Err(Error::invalid_enum_value()), result);
-        let result2 = TestEnumBad::Value.write_json(&mut buffer);
-        assert_eq!(Err(Error::invalid_enum_value()), result2);
+    fn test_tuple_structs() {
+        let mut actual: Vec<u8> = Vec::new();
+        TestNewtype(vec![1, 2, 3, 4])
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![1, 2, 3, 4], actual);
+
+        let to_ser = TestTuple(7, TestNewtype(vec![0, 0, 0, 255]), "a".to_string());
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_xdr(&mut actual).unwrap();
+        assert_eq!(
+            vec![0, 0, 0, 7, 0, 0, 0, 255, 0, 0, 0, 1, 97, 0, 0, 0],
+            actual
+        );
+        let mut actual: Vec<u8> = Vec::new();
+        to_ser.write_json(&mut actual).unwrap();
+        let expected: Vec<u8> = br#"[7,"000000ff","a"]"#.to_vec();
+        assert_json!(expected, actual);
     }
 
     #[derive(XDROut)]
@@ -1128,4 +1858,501 @@ mod tests {
             .unwrap();
         assert_json!(expected_second, actual_second);
     }
+
+    #[derive(XDROut)]
+    enum TestKind {
+        Payment = 0,
+        Create = 1,
+    }
+
+    #[derive(XDROut)]
+    enum TestEnumUnion {
+        #[discriminant(enum = "TestKind::Payment")]
+        Payment(u32),
+        #[discriminant(enum = "TestKind::Create")]
+        Create(()),
+    }
+
+    #[derive(XDROut)]
+    enum TestUnsignedUnion {
+        #[discriminant(unsigned = "4294967295")]
+        Max(u32),
+    }
+
+    #[derive(XDROut)]
+    enum TestBoolUnion {
+        #[discriminant(bool = "true")]
+        Yes(u32),
+        #[discriminant(bool = "false")]
+        No(()),
+    }
+
+    #[test]
+    fn test_union_typed_discriminant() {
+        let mut actual: Vec<u8> = Vec::new();
+        TestEnumUnion::Payment(5).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 0, 0, 0, 0, 5], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestEnumUnion::Create(()).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 1], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnsignedUnion::Max(3).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![255, 255, 255, 255, 0, 0, 0, 3], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestBoolUnion::Yes(3).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 3], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestBoolUnion::No(()).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 0], actual);
+    }
+
+    #[test]
+    fn test_union_typed_discriminant_json() {
+        let expected: Vec<u8> = r#"{"type":0,"data":5}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestEnumUnion::Payment(5).write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+
+        let expected: Vec<u8> = r#"{"type":4294967295,"data":3}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnsignedUnion::Max(3).write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+
+        let expected: Vec<u8> = r#"{"type":false,"data":""}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestBoolUnion::No(()).write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+    }
+
+    #[derive(XDROut)]
+    enum TestUnionDefault {
+        #[discriminant(value = "1")]
+        Known(u32),
+        #[discriminant(default)]
+        Unknown(i32, u32),
+    }
+
+    #[derive(XDROut)]
+    enum TestUnionVoidDefault {
+        #[discriminant(value = "1")]
+        Known(u32),
+        #[discriminant(default)]
+        Unknown(i32),
+    }
+
+    #[test]
+    fn test_union_default() {
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionDefault::Known(3).write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 3], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionVoidDefault::Known(3)
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 3], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        let written = TestUnionDefault::Unknown(7, 3)
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![0, 0, 0, 7, 0, 0, 0, 3], actual);
+        assert_eq!(8, written);
+
+        let mut actual: Vec<u8> = Vec::new();
+        let written = TestUnionVoidDefault::Unknown(-2)
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![255, 255, 255, 254], actual);
+        assert_eq!(4, written);
+    }
+
+    #[test]
+    fn test_union_default_json() {
+        let expected: Vec<u8> = r#"{"type":7,"data":3}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionDefault::Unknown(7, 3)
+            .write_json(&mut actual)
+            .unwrap();
+        assert_json!(expected, actual);
+
+        let expected: Vec<u8> = r#"{"type":-2,"data":""}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionVoidDefault::Unknown(-2)
+            .write_json(&mut actual)
+            .unwrap();
+        assert_json!(expected, actual);
+    }
+
+    #[derive(XDROut)]
+    enum TestUnionFields {
+        #[discriminant(value = "0")]
+        Pay {
+            to: u32,
+            #[array(var = 8)]
+            memo: String,
+        },
+        #[discriminant(value = "1")]
+        Swap(u32, #[array(fixed = 2)] Vec<u8>),
+    }
+
+    #[test]
+    fn test_union_fields() {
+        let mut actual: Vec<u8> = Vec::new();
+        let written = TestUnionFields::Pay {
+            to: 3,
+            memo: "hi".to_string(),
+        }
+        .write_xdr(&mut actual)
+        .unwrap();
+        assert_eq!(
+            vec![0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 2, 104, 105, 0, 0],
+            actual
+        );
+        assert_eq!(16, written);
+
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionFields::Swap(3, vec![7, 8])
+            .write_xdr(&mut actual)
+            .unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 3, 7, 8, 0, 0], actual);
+
+        let mut actual: Vec<u8> = Vec::new();
+        let result = TestUnionFields::Swap(3, vec![7]).write_xdr(&mut actual);
+        assert_eq!(Err(Error::fixed_array_wrong_size()), result);
+    }
+
+    #[test]
+    fn test_union_fields_json() {
+        let expected: Vec<u8> = r#"{"type":0,"data":{"to":3,"memo":"hi"}}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionFields::Pay {
+            to: 3,
+            memo: "hi".to_string(),
+        }
+        .write_json(&mut actual)
+        .unwrap();
+        assert_json!(expected, actual);
+
+        let expected: Vec<u8> = r#"{"type":1,"data":[3,"0708"]}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestUnionFields::Swap(3, vec![7, 8])
+            .write_json(&mut actual)
+            .unwrap();
+        assert_json!(expected, actual);
+    }
+
+    // Writes a `u16`, which XDR lacks, as an unsigned int.
+    mod as_u32 {
+        use crate::error::Error;
+        use crate::ser::XDROut;
+        use std::io::Write;
+
+        pub fn write_xdr<W: Write + ?Sized>(val: &u16, out: &mut W) -> Result<u64, Error> {
+            u32::from(*val).write_xdr(out)
+        }
+
+        pub fn write_json<W: Write + ?Sized>(val: &u16, out: &mut W) -> Result<u64, Error> {
+            u32::from(*val).write_json(out)
+        }
+
+        pub fn xdr_size(val: &u16) -> u64 {
+            u32::from(*val).xdr_size()
+        }
+    }
+
+    #[derive(XDROut)]
+    struct TestFieldAttrs {
+        #[xdr(rename = "type")]
+        kind: u32,
+        #[xdr(skip)]
+        cache: Vec<u32>,
+        #[xdr(with = "as_u32")]
+        port: u16,
+    }
+
+    #[test]
+    fn test_field_attrs() {
+        let value = TestFieldAttrs {
+            kind: 1,
+            cache: vec![1, 2],
+            port: 80,
+        };
+        let mut actual: Vec<u8> = Vec::new();
+        let written = value.write_xdr(&mut actual).unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 0, 0, 80], actual);
+        assert_eq!(8, written);
+        assert_eq!(2, value.cache.len());
+    }
+
+    #[test]
+    fn test_field_attrs_json() {
+        let expected: Vec<u8> = r#"{"type":1,"port":80}"#.as_bytes().to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        TestFieldAttrs {
+            kind: 1,
+            cache: vec![1, 2],
+            port: 80,
+        }
+        .write_json(&mut actual)
+        .unwrap();
+        assert_json!(expected, actual);
+    }
+
+    #[derive(XDROut)]
+    struct TestBounded {
+        hash: [u8; 3],
+        points: [i32; 2],
+        memo: VarOpaque<4>,
+        signers: VarArray<u32, 2>,
+        name: BoundedString<4>,
+    }
+
+    fn bounded() -> TestBounded {
+        TestBounded {
+            hash: [1, 2, 3],
+            points: [4, -1],
+            memo: VarOpaque::new(vec![9]).unwrap(),
+            signers: VarArray::new(vec![7]).unwrap(),
+            name: BoundedString::new("hi".to_string()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_bounded_types() {
+        let mut actual: Vec<u8> = Vec::new();
+        let written = bounded().write_xdr(&mut actual).unwrap();
+        assert_eq!(
+            vec![
+                1, 2, 3, 0, 0, 0, 0, 4, 255, 255, 255, 255, 0, 0, 0, 1, 9, 0, 0, 0, 0, 0, 0, 1, 0,
+                0, 0, 7, 0, 0, 0, 2, 104, 105, 0, 0
+            ],
+            actual
+        );
+        assert_eq!(36, written);
+    }
+
+    #[test]
+    fn test_bounded_types_json() {
+        let expected: Vec<u8> =
+            r#"{"hash":"010203","points":[4,-1],"memo":"CQ==","signers":[7],"name":"hi"}"#
+                .as_bytes()
+                .to_vec();
+        let mut actual: Vec<u8> = Vec::new();
+        bounded().write_json(&mut actual).unwrap();
+        assert_json!(expected, actual);
+    }
+
+    #[derive(XDROut)]
+    struct TestNestedBounds {
+        #[array(var = 2)]
+        signers: Vec<VarOpaque<2>>,
+        #[array(var = 2)]
+        memo: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn test_nested_bounds() {
+        let value = TestNestedBounds {
+            signers: vec![VarOpaque::new(vec![1]).unwrap()],
+            memo: Some(vec![5]),
+        };
+        let mut actual: Vec<u8> = Vec::new();
+        let written = value.write_xdr(&mut actual).unwrap();
+        assert_eq!(
+            vec![0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 5, 0, 0, 0],
+            actual
+        );
+        assert_eq!(24, written);
+
+        let value = TestNestedBounds {
+            signers: vec![],
+            memo: Some(vec![1, 2, 3]),
+        };
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            value.write_xdr(&mut Vec::new())
+        );
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            value.write_json(&mut Vec::new())
+        );
+        let value = TestNestedBounds {
+            signers: vec![],
+            memo: None,
+        };
+        assert_eq!(
+            r#"{"signers":[],"memo":[]}"#,
+            to_json_string(&value).unwrap()
+        );
+    }
+
+    #[derive(XDROut)]
+    struct TestBorrowed<'a> {
+        #[array(var = 2)]
+        memo: &'a [u8],
+        name: Cow<'a, str>,
+        #[array(fixed = 1)]
+        hash: &'a [u8],
+    }
+
+    #[test]
+    fn test_borrowed_types() {
+        let value = TestBorrowed {
+            memo: &[1],
+            name: Cow::Borrowed("hi"),
+            hash: &[2],
+        };
+        let mut actual: Vec<u8> = Vec::new();
+        let written = value.write_xdr(&mut actual).unwrap();
+        assert_eq!(
+            vec![0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 2, 104, 105, 0, 0, 2, 0, 0, 0],
+            actual
+        );
+        assert_eq!(20, written);
+        assert_eq!(
+            r#"{"memo":"AQ==","name":"hi","hash":"02"}"#,
+            to_json_string(&value).unwrap()
+        );
+
+        let value = TestBorrowed {
+            memo: &[1, 2, 3],
+            name: Cow::Owned("hi".to_string()),
+            hash: &[2],
+        };
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            value.write_xdr(&mut Vec::new())
+        );
+        assert_eq!(to_xdr_bytes(&"hi".to_string()), to_xdr_bytes("hi"));
+    }
+
+    fn assert_xdr_size<T: XDROut + ?Sized>(val: &T) {
+        let encoded = to_xdr_bytes(val).unwrap();
+        assert_eq!(encoded.len() as u64, val.xdr_size());
+    }
+
+    #[test]
+    fn test_xdr_size() {
+        assert_xdr_size(&true);
+        assert_xdr_size(&-1i32);
+        assert_xdr_size(&1u64);
+        assert_xdr_size(&Quad::from(1.5));
+        assert_xdr_size(&());
+        assert_xdr_size(&"hello".to_string());
+        assert_xdr_size("hi");
+        assert_xdr_size(&vec![1u8, 2, 3]);
+        assert_xdr_size(&[1u8, 2, 3, 4, 5][..]);
+        assert_xdr_size(&vec![1u32, 2]);
+        assert_xdr_size(&vec!["a".to_string(), "bcde".to_string()]);
+        assert_xdr_size(&[1u8; 3]);
+        assert_xdr_size(&[1i32; 2]);
+        assert_xdr_size(&Some(5u32));
+        assert_xdr_size(&None::<u32>);
+        assert_xdr_size(&Box::new("abc".to_string()));
+        assert_xdr_size(&Cow::Borrowed("abc"));
+
+        assert_xdr_size(&TestStruct { one: 1.0, two: 2 });
+        assert_xdr_size(&TestFixed {
+            vector: vec![1, 2, 3],
+        });
+        assert_xdr_size(&TestTuple(
+            7,
+            TestNewtype(vec![0, 0, 0, 255]),
+            "a".to_string(),
+        ));
+        assert_xdr_size(&TestEnum::One);
+        assert_xdr_size(&TestUnion::Second(TestStruct { one: 1.0, two: 2 }));
+        assert_xdr_size(&TestUnionDefault::Unknown(7, 3));
+        assert_xdr_size(&TestUnionVoidDefault::Unknown(-2));
+        assert_xdr_size(&TestUnionFields::Pay {
+            to: 3,
+            memo: "hi".to_string(),
+        });
+        assert_xdr_size(&TestUnionFields::Swap(3, vec![7, 8]));
+        assert_xdr_size(&TestFieldAttrs {
+            kind: 1,
+            cache: vec![1, 2],
+            port: 80,
+        });
+        assert_xdr_size(&bounded());
+        assert_xdr_size(&TestNestedBounds {
+            signers: vec![VarOpaque::new(vec![1, 2]).unwrap()],
+            memo: Some(vec![5]),
+        });
+        assert_xdr_size(&TestBorrowed {
+            memo: &[1],
+            name: Cow::Borrowed("hello"),
+            hash: &[2],
+        });
+    }
+
+    // Computed at compile time.
+    const STRUCT_SIZE: Option<usize> = TestStruct::XDR_SIZE;
+
+    #[test]
+    fn test_xdr_size_const() {
+        assert_eq!(Some(8), STRUCT_SIZE);
+        assert_eq!(Some(4), u32::XDR_SIZE);
+        assert_eq!(Some(16), Quad::XDR_SIZE);
+        assert_eq!(Some(4), <[u8; 3]>::XDR_SIZE);
+        assert_eq!(Some(8), <[i32; 2]>::XDR_SIZE);
+        assert_eq!(None, <[String; 2]>::XDR_SIZE);
+        assert_eq!(None, String::XDR_SIZE);
+        assert_eq!(None, Vec::<u8>::XDR_SIZE);
+        assert_eq!(None, Option::<u32>::XDR_SIZE);
+        assert_eq!(Some(4), Box::<u32>::XDR_SIZE);
+
+        assert_eq!(Some(12), TestFixed::XDR_SIZE);
+        assert_eq!(Some(32), TestFixedSingle::XDR_SIZE);
+        assert_eq!(Some(4), TestNewtype::XDR_SIZE);
+        assert_eq!(None, TestTuple::XDR_SIZE);
+        assert_eq!(None, TestFieldAttrs::XDR_SIZE);
+        assert_eq!(None, TestBounded::XDR_SIZE);
+
+        assert_eq!(Some(4), TestEnum::XDR_SIZE);
+        assert_eq!(Some(8), TestUnionDefault::XDR_SIZE);
+        assert_eq!(None, TestUnionVoidDefault::XDR_SIZE);
+        assert_eq!(None, TestUnion::XDR_SIZE);
+        assert_eq!(None, TestUnionFields::XDR_SIZE);
+    }
+
+    #[test]
+    fn test_to_xdr_slice() {
+        let value = TestStruct { one: 1.0, two: 2 };
+        let mut buffer = [0xffu8; 10];
+        assert_eq!(Ok(8), to_xdr_slice(&value, &mut buffer));
+        assert_eq!([0x3f, 0x80, 0, 0, 0, 0, 0, 2, 0xff, 0xff], buffer);
+
+        let mut buffer = [0xffu8; 7];
+        assert_eq!(
+            Err(Error::buffer_too_small()),
+            to_xdr_slice(&value, &mut buffer)
+        );
+        assert_eq!([0xff; 7], buffer);
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(Ok(8), to_xdr_slice("hi", &mut buffer));
+        assert_eq!([0, 0, 0, 2, 104, 105, 0, 0], buffer);
+        let value = TestFixed { vector: vec![1] };
+        assert_eq!(
+            Err(Error::fixed_array_wrong_size()),
+            to_xdr_slice(&value, &mut [0u8; 16])
+        );
+
+        let value = TestNestedBounds {
+            signers: vec![],
+            memo: Some(vec![1, 2, 3]),
+        };
+        let mut buffer = [0xffu8; 16];
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            to_xdr_slice(&value, &mut buffer)
+        );
+        assert_eq!([0, 0, 0, 0, 0, 0, 0, 1, 0xff], buffer[..9]);
+    }
 }
@@ -0,0 +1,321 @@
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+use crate::error::Error;
+
+/// XDR quadruple-precision floating point number.
+///
+/// Values are stored as the raw IEEE 754 binary128 bits, as Rust has no
+/// native 128-bit float. Every `f64` converts exactly into a `Quad`; going
+/// back is only possible when no precision or range is lost, see `to_f64`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Quad(u128);
+
+const F64_MANTISSA_BITS: u32 = 52;
+const F64_EXP_BIAS: i32 = 1023;
+const QUAD_MANTISSA_BITS: u32 = 112;
+const QUAD_EXP_BIAS: i32 = 16383;
+const QUAD_EXP_MAX: u128 = 0x7fff;
+const QUAD_MANTISSA_MASK: u128 = (1 << QUAD_MANTISSA_BITS) - 1;
+const MANTISSA_SHIFT: u32 = QUAD_MANTISSA_BITS - F64_MANTISSA_BITS;
+
+impl Quad {
+    pub const fn from_bits(bits: u128) -> Self {
+        Quad(bits)
+    }
+
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// Converts to `f64` if the value is exactly representable, including
+    /// infinities and NaNs whose payload fits.
+    pub fn to_f64(self) -> Option<f64> {
+        let sign = ((self.0 >> 127) as u64) << 63;
+        let exp = (self.0 >> QUAD_MANTISSA_BITS) & QUAD_EXP_MAX;
+        let mantissa = self.0 & QUAD_MANTISSA_MASK;
+        let dropped = (1 << MANTISSA_SHIFT) - 1;
+
+        if exp == QUAD_EXP_MAX {
+            if mantissa & dropped != 0 {
+                return None;
+            }
+            let bits = sign | 0x7ff << F64_MANTISSA_BITS | (mantissa >> MANTISSA_SHIFT) as u64;
+            return Some(f64::from_bits(bits));
+        }
+        if exp == 0 {
+            // Quad subnormals are far below the smallest f64.
+            return match mantissa {
+                0 => Some(f64::from_bits(sign)),
+                _ => None,
+            };
+        }
+
+        let unbiased = exp as i32 - QUAD_EXP_BIAS;
+        if unbiased > F64_EXP_BIAS {
+            return None;
+        }
+        if unbiased > -F64_EXP_BIAS {
+            if mantissa & dropped != 0 {
+                return None;
+            }
+            let exp = ((unbiased + F64_EXP_BIAS) as u64) << F64_MANTISSA_BITS;
+            return Some(f64::from_bits(
+                sign | exp | (mantissa >> MANTISSA_SHIFT) as u64,
+            ));
+        }
+
+        // Subnormal in f64: the implicit leading bit becomes explicit.
+        let shift =
+            QUAD_MANTISSA_BITS as i32 - (unbiased + F64_EXP_BIAS - 1 + F64_MANTISSA_BITS as i32);
+        if shift > QUAD_MANTISSA_BITS as i32 {
+            return None;
+        }
+        let significand = mantissa | 1 << QUAD_MANTISSA_BITS;
+        if significand & ((1 << shift) - 1) != 0 {
+            return None;
+        }
+        Some(f64::from_bits(sign | (significand >> shift) as u64))
+    }
+}
+
+impl From<f64> for Quad {
+    fn from(val: f64) -> Self {
+        let bits = val.to_bits();
+        let sign = ((bits >> 63) as u128) << 127;
+        let exp = (bits >> F64_MANTISSA_BITS) & 0x7ff;
+        let mantissa = (bits & ((1 << F64_MANTISSA_BITS) - 1)) as u128;
+
+        let (exp, mantissa) = match (exp, mantissa) {
+            (0x7ff, _) => (QUAD_EXP_MAX, mantissa << MANTISSA_SHIFT),
+            (0, 0) => (0, 0),
+            (0, _) => {
+                // Normalise the f64 subnormal around its highest set bit.
+                let top = 127 - mantissa.leading_zeros();
+                let exp =
+                    top as i32 - (F64_EXP_BIAS - 1 + F64_MANTISSA_BITS as i32) + QUAD_EXP_BIAS;
+                let mantissa = (mantissa << (QUAD_MANTISSA_BITS - top)) & QUAD_MANTISSA_MASK;
+                (exp as u128, mantissa)
+            }
+            _ => (
+                (exp as i32 - F64_EXP_BIAS + QUAD_EXP_BIAS) as u128,
+                mantissa << MANTISSA_SHIFT,
+            ),
+        };
+        Quad(sign | exp << QUAD_MANTISSA_BITS | mantissa)
+    }
+}
+
+/// XDR variable-length opaque data of at most `MAX` bytes, `opaque<MAX>`.
+///
+/// Fixed-length opaque data is a `[u8; N]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VarOpaque<const MAX: u32>(Vec<u8>);
+
+impl<const MAX: u32> VarOpaque<MAX> {
+    /// Fails with `BadArraySize` if `bytes` is longer than `MAX`.
+    pub fn new(bytes: Vec<u8>) -> Result<Self, Error> {
+        if bytes.len() as u64 > MAX as u64 {
+            return Err(Error::bad_array_size());
+        }
+        Ok(VarOpaque(bytes))
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl<const MAX: u32> TryFrom<Vec<u8>> for VarOpaque<MAX> {
+    type Error = Error;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Error> {
+        VarOpaque::new(bytes)
+    }
+}
+
+impl<const MAX: u32> Deref for VarOpaque<MAX> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// XDR variable-length array of at most `MAX` elements, `T<MAX>`.
+///
+/// Fixed-length arrays are a `[T; N]`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VarArray<T, const MAX: u32>(Vec<T>);
+
+impl<T, const MAX: u32> VarArray<T, MAX> {
+    /// Fails with `BadArraySize` if `items` has more than `MAX` elements.
+    pub fn new(items: Vec<T>) -> Result<Self, Error> {
+        if items.len() as u64 > MAX as u64 {
+            return Err(Error::bad_array_size());
+        }
+        Ok(VarArray(items))
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T, const MAX: u32> Default for VarArray<T, MAX> {
+    fn default() -> Self {
+        VarArray(Vec::new())
+    }
+}
+
+impl<T, const MAX: u32> TryFrom<Vec<T>> for VarArray<T, MAX> {
+    type Error = Error;
+
+    fn try_from(items: Vec<T>) -> Result<Self, Error> {
+        VarArray::new(items)
+    }
+}
+
+impl<T, const MAX: u32> Deref for VarArray<T, MAX> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+/// XDR string of at most `MAX` bytes, `string<MAX>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BoundedString<const MAX: u32>(String);
+
+impl<const MAX: u32> BoundedString<MAX> {
+    /// Fails with `VarArrayWrongSize` if `string` is longer than `MAX`
+    /// bytes.
+    pub fn new(string: String) -> Result<Self, Error> {
+        if string.len() as u64 > MAX as u64 {
+            return Err(Error::var_array_wrong_size());
+        }
+        Ok(BoundedString(string))
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<const MAX: u32> TryFrom<String> for BoundedString<MAX> {
+    type Error = Error;
+
+    fn try_from(string: String) -> Result<Self, Error> {
+        BoundedString::new(string)
+    }
+}
+
+impl<const MAX: u32> TryFrom<&str> for BoundedString<MAX> {
+    type Error = Error;
+
+    fn try_from(string: &str) -> Result<Self, Error> {
+        BoundedString::new(string.to_string())
+    }
+}
+
+impl<const MAX: u32> Deref for BoundedString<MAX> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quad_from_f64() {
+        assert_eq!(
+            0x3fff_0000_0000_0000_0000_0000_0000_0000,
+            Quad::from(1.0).to_bits()
+        );
+        assert_eq!(
+            0xc000_0000_0000_0000_0000_0000_0000_0000,
+            Quad::from(-2.0).to_bits()
+        );
+        assert_eq!(0, Quad::from(0.0).to_bits());
+        assert_eq!(1 << 127, Quad::from(-0.0).to_bits());
+        assert_eq!(
+            0x7fff_0000_0000_0000_0000_0000_0000_0000,
+            Quad::from(f64::INFINITY).to_bits()
+        );
+        assert_eq!(
+            0x3bcd_0000_0000_0000_0000_0000_0000_0000,
+            Quad::from(f64::from_bits(1)).to_bits()
+        );
+    }
+
+    #[test]
+    fn test_quad_to_f64() {
+        let values = [
+            1.0,
+            -2.5,
+            0.1,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::from_bits(1),
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for val in values.iter() {
+            assert_eq!(Some(*val), Quad::from(*val).to_f64());
+        }
+        assert!(Quad::from(f64::NAN).to_f64().unwrap().is_nan());
+        assert_eq!(
+            Some((-0.0f64).to_bits()),
+            Quad::from(-0.0).to_f64().map(f64::to_bits)
+        );
+    }
+
+    #[test]
+    fn test_quad_to_f64_inexact() {
+        // 1 + 2^-112 needs more mantissa than f64 has.
+        assert_eq!(
+            None,
+            Quad::from_bits(0x3fff_0000_0000_0000_0000_0000_0000_0001).to_f64()
+        );
+        // 2^1024 is beyond f64 range.
+        assert_eq!(
+            None,
+            Quad::from_bits(0x43ff_0000_0000_0000_0000_0000_0000_0000).to_f64()
+        );
+        // 2^-1075 is below the smallest f64 subnormal.
+        assert_eq!(
+            None,
+            Quad::from_bits(0x3bcc_0000_0000_0000_0000_0000_0000_0000).to_f64()
+        );
+        // Smallest quad subnormal.
+        assert_eq!(None, Quad::from_bits(1).to_f64());
+    }
+
+    #[test]
+    fn test_bounded_new() {
+        assert_eq!(&[1, 2][..], &*VarOpaque::<2>::new(vec![1, 2]).unwrap());
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            VarOpaque::<2>::new(vec![1, 2, 3])
+        );
+        assert_eq!(
+            vec![1],
+            VarArray::<u32, 1>::new(vec![1]).unwrap().into_inner()
+        );
+        assert_eq!(
+            Err(Error::bad_array_size()),
+            VarArray::<u32, 1>::new(vec![1, 2])
+        );
+        assert_eq!("hi", &*BoundedString::<2>::try_from("hi").unwrap());
+        assert_eq!(
+            Err(Error::var_array_wrong_size()),
+            BoundedString::<2>::try_from("hey")
+        );
+    }
+}
@@ -0,0 +1,104 @@
+// Encoding into a slice must not touch the heap. The allocator counts the
+// allocations made by the current thread while counting is switched on, so
+// it lives in its own test binary.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use xdr_rs_serialize::ser::{to_xdr_slice, XDROut};
+use xdr_rs_serialize::types::{BoundedString, VarOpaque};
+use xdr_rs_serialize_derive::XDROut;
+
+struct CountingAlloc;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.with(|count| count.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[derive(XDROut)]
+enum Kind {
+    Payment = 0,
+}
+
+#[derive(XDROut)]
+enum Body {
+    #[discriminant(value = "0")]
+    Memo(String),
+    #[discriminant(value = "1")]
+    Transfer {
+        to: [u8; 4],
+        #[array(var = 8)]
+        note: String,
+    },
+}
+
+#[derive(XDROut)]
+struct Envelope {
+    kind: Kind,
+    #[array(fixed = 3)]
+    hash: Vec<u8>,
+    #[array(var = 16)]
+    name: String,
+    tags: Vec<String>,
+    memo: VarOpaque<8>,
+    label: BoundedString<8>,
+    body: Body,
+    next: Option<Box<u64>>,
+}
+
+#[test]
+fn test_encode_without_allocating() {
+    let envelope = Envelope {
+        kind: Kind::Payment,
+        hash: vec![1, 2, 3],
+        name: "alice".to_string(),
+        tags: vec!["a".to_string(), "bc".to_string()],
+        memo: VarOpaque::new(vec![9; 5]).unwrap(),
+        label: BoundedString::new("hi".to_string()).unwrap(),
+        body: Body::Transfer {
+            to: [7; 4],
+            note: "thanks".to_string(),
+        },
+        next: Some(Box::new(5)),
+    };
+    let memo = Body::Memo("m".to_string());
+    let mut buffer = [0u8; 256];
+    let mut written = 0;
+    let count = allocations(|| {
+        written = to_xdr_slice(&envelope, &mut buffer).unwrap();
+        envelope.write_xdr(&mut &mut buffer[written..]).unwrap();
+        memo.write_xdr(&mut &mut buffer[..]).unwrap();
+    });
+    assert_eq!(0, count);
+    assert_eq!(envelope.xdr_size() as usize, written);
+
+    let mut small = [0u8; 8];
+    let count = allocations(|| {
+        assert!(to_xdr_slice(&envelope, &mut small).is_err());
+    });
+    assert_eq!(0, count);
+}
@@ -0,0 +1,94 @@
+// Generated by xdr-rs-serialize. Do not edit.
+
+#[allow(unused_imports)]
+use xdr_rs_serialize::types::Quad;
+use xdr_rs_serialize_derive::{XDRIn, XDROut};
+
+#[allow(dead_code)]
+pub const HASH_SIZE: i64 = 4;
+
+#[allow(dead_code)]
+pub const MAX_SIGNERS: i64 = 2;
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct Hash(#[array(fixed = 4)] pub Vec<u8>);
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct Name(#[array(var = 32)] pub String);
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Kind {
+    PAYMENT = 0,
+    MEMO = 1,
+    NOTE = 5,
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub struct Signer {
+    pub key: Hash,
+    pub weight: u32,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Body {
+    #[discriminant(enum = "Kind::PAYMENT")]
+    PAYMENT(BodyPayment),
+    #[discriminant(enum = "Kind::MEMO")]
+    MEMO(BodyMemo),
+    #[discriminant(enum = "Kind::NOTE")]
+    NOTE(()),
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub struct BodyPayment {
+    pub amount: i64,
+    #[array(var = 2)]
+    pub signers: Vec<Signer>,
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+#[xdr(transparent)]
+pub struct BodyMemo(#[array(var = 8)] pub Vec<u8>);
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Ext {
+    #[discriminant(value = "0")]
+    V0(()),
+    #[discriminant(default)]
+    label(i32, Name),
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Flag {
+    #[discriminant(bool = "true")]
+    TRUE(i32),
+    #[discriminant(bool = "false")]
+    FALSE(()),
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub enum Code {
+    #[discriminant(unsigned = "0")]
+    V0(()),
+    #[discriminant(unsigned = "4294967295")]
+    big(u64),
+}
+
+#[derive(Clone, Debug, PartialEq, XDROut, XDRIn)]
+pub struct Transaction {
+    pub source: Name,
+    pub body: Body,
+    pub ext: Ext,
+    pub flag: Flag,
+    pub code: Code,
+    pub next: Option<Box<Transaction>>,
+    pub r#final: bool,
+    pub fee: f64,
+}
@@ -0,0 +1,56 @@
+const HASH_SIZE = 4;
+const MAX_SIGNERS = 2;
+
+typedef opaque Hash[HASH_SIZE];
+typedef string Name<32>;
+
+enum Kind { PAYMENT, MEMO, NOTE = 5 };
+
+struct Signer {
+    Hash key;
+    unsigned int weight;
+};
+
+union Body switch (Kind kind) {
+    case PAYMENT:
+        struct {
+            hyper amount;
+            Signer signers<MAX_SIGNERS>;
+        } payment;
+    case MEMO:
+        opaque memo<8>;
+    case NOTE:
+        void;
+};
+
+union Ext switch (int v) {
+    case 0:
+        void;
+    default:
+        Name label;
+};
+
+union Flag switch (bool set) {
+    case TRUE:
+        int count;
+    case FALSE:
+        void;
+};
+
+union Code switch (unsigned int code) {
+    case 0:
+        void;
+    case 0xFFFFFFFF:
+        unsigned hyper big;
+};
+
+struct Transaction {
+    Name source;
+    Body body;
+    Ext ext;
+    Flag flag;
+    Code code;
+    Transaction *next;
+    bool final;
+    double fee;
+};
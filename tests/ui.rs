@@ -0,0 +1,9 @@
+// Invalid derive input must be reported as a compile error pointing at the
+// offending attribute or field, and the cases in `pass` must compile and run.
+// Regenerate the expected output with `TRYBUILD=overwrite cargo test --test ui`.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    t.pass("tests/ui/pass/*.rs");
+}
@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+struct Payload {
+    #[array(fixed = "4")]
+    data: Vec<u8>,
+}
+
+fn main() {}
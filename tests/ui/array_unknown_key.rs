@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDRIn;
+
+#[derive(XDRIn)]
+struct Payload {
+    #[array(max = 4)]
+    data: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+struct Payload {
+    #[array(var = 4)]
+    count: u32,
+}
+
+fn main() {}
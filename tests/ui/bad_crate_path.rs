@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+#[xdr(crate = "not a path")]
+struct Payload {
+    count: u32,
+}
+
+fn main() {}
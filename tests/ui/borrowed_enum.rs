@@ -0,0 +1,8 @@
+use xdr_rs_serialize_derive::XDRInBorrowed;
+
+#[derive(XDRInBorrowed)]
+enum Body<'a> {
+    Memo(&'a [u8]),
+}
+
+fn main() {}
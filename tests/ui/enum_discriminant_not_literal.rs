@@ -0,0 +1,10 @@
+use xdr_rs_serialize_derive::XDROut;
+
+const RED: isize = 0;
+
+#[derive(XDROut)]
+enum Color {
+    Red = RED,
+}
+
+fn main() {}
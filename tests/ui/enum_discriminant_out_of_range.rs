@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Kind {
+    Low = -2147483649,
+    High = 2147483647,
+}
+
+fn main() {}
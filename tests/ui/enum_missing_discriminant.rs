@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Color {
+    Red = 0,
+    Green,
+}
+
+fn main() {}
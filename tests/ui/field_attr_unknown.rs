@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+struct Payment {
+    #[xdr(flatten)]
+    amount: u64,
+}
+
+fn main() {}
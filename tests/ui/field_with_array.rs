@@ -0,0 +1,10 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+struct Payment {
+    #[xdr(with = "hex_memo")]
+    #[array(var = 28)]
+    memo: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use xdr_rs_serialize::de::XDRIn;
+use xdr_rs_serialize::ser::{to_xdr_bytes, XDROut};
+use xdr_rs_serialize_derive::{XDRIn, XDROut};
+
+type Code = u32;
+
+#[derive(XDROut, XDRIn, Debug, PartialEq)]
+enum Body {
+    #[discriminant(value = "0")]
+    Amount(u32),
+    #[discriminant(default)]
+    Other(std::primitive::i32, u32),
+}
+
+#[derive(XDROut, XDRIn, Debug, PartialEq)]
+enum Reply {
+    #[discriminant(unsigned = "0")]
+    Ok(()),
+    #[discriminant(default)]
+    Failed(Code),
+}
+
+fn main() {
+    let body = Body::Other(-3, 7);
+    let bytes = to_xdr_bytes(&body).unwrap();
+    assert_eq!(body.xdr_size(), bytes.len() as u64);
+    assert_eq!((body, 8), Body::read_xdr(&bytes).unwrap());
+    let reply = Reply::Failed(9);
+    assert_eq!((reply, 4), Reply::read_xdr(&to_xdr_bytes(&Reply::Failed(9)).unwrap()).unwrap());
+}
@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+#[xdr(transparent)]
+enum Kind {
+    A = 0,
+}
+
+fn main() {}
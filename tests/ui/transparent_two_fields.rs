@@ -0,0 +1,10 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+#[xdr(transparent)]
+struct Pair {
+    first: u32,
+    second: u32,
+}
+
+fn main() {}
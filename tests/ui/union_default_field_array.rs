@@ -0,0 +1,11 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Body {
+    #[discriminant(value = "0")]
+    Amount(u32),
+    #[discriminant(default)]
+    Other(i32, #[array(var = 2)] Vec<u8>),
+}
+
+fn main() {}
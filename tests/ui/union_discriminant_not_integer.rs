@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDRIn;
+
+#[derive(XDRIn)]
+enum Body {
+    #[discriminant(value = "one")]
+    Amount(u32),
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Body {
+    #[discriminant(value = "2147483648")]
+    Amount(u32),
+}
+
+#[derive(XDROut)]
+enum Reply {
+    #[discriminant(unsigned = "-1")]
+    Failed(u32),
+}
+
+fn main() {}
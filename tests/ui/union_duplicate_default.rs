@@ -0,0 +1,11 @@
+use xdr_rs_serialize_derive::XDRIn;
+
+#[derive(XDRIn)]
+enum Body {
+    #[discriminant(default)]
+    Other(i32),
+    #[discriminant(default)]
+    Unknown(i32, u32),
+}
+
+fn main() {}
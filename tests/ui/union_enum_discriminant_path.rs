@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDRIn;
+
+#[derive(XDRIn)]
+enum Body {
+    #[discriminant(enum = "Payment")]
+    Payment(u32),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Body {
+    #[discriminant(unsigned = "1")]
+    Amount(u32),
+    #[discriminant(bool = "true")]
+    Flag(()),
+}
+
+fn main() {}
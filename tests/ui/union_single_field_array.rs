@@ -0,0 +1,9 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+enum Body {
+    #[discriminant(value = "0")]
+    Data(#[array(var = 2)] Vec<u8>),
+}
+
+fn main() {}
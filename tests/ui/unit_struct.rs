@@ -0,0 +1,6 @@
+use xdr_rs_serialize_derive::XDROut;
+
+#[derive(XDROut)]
+struct Empty;
+
+fn main() {}
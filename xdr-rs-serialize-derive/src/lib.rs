@@ -3,131 +3,492 @@
 extern crate proc_macro;
 
 use crate::proc_macro::TokenStream;
-use quote::quote;
 use quote::ToTokens;
-use syn;
+use quote::{format_ident, quote, quote_spanned};
+use std::convert::TryFrom;
+use syn::spanned::Spanned;
 use syn::Meta::{List, NameValue};
 use syn::NestedMeta::Meta;
 
-#[proc_macro_derive(XDROut, attributes(array, discriminant))]
+#[proc_macro_derive(XDROut, attributes(array, discriminant, xdr))]
 pub fn xdr_out_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     impl_xdr_out_macro(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
 
-#[proc_macro_derive(XDRIn, attributes(array, discriminant))]
+#[proc_macro_derive(XDRIn, attributes(array, discriminant, xdr))]
 pub fn xdr_in_macro_derive(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
 
     impl_xdr_in_macro(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(XDRInBorrowed, attributes(array, discriminant, xdr))]
+pub fn xdr_in_borrowed_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    impl_xdr_in_borrowed_macro(&ast)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
 }
 
 #[derive(Debug, Clone)]
 struct Member {
+    /// Binding for the member's value, named `field{n}` for tuple structs.
     pub name: proc_macro2::Ident,
+    /// The member as written after `self.`, e.g. `r#type` or `0`.
+    pub access: String,
+    /// The member's JSON key and error path segment.
+    pub key: String,
+    /// The member's value when writing, e.g. `self.amount`.
+    pub value: String,
     pub v_type: proc_macro2::TokenStream,
     pub fixed: u32,
     pub var: u32,
+    /// Left out of both encodings and filled from `Default` when decoding.
+    pub skip: bool,
+    /// Filled from `Default` when the key is missing from the JSON.
+    pub default: bool,
+    /// A module whose functions encode and decode the member in place of
+    /// its `XDROut` and `XDRIn` impls.
+    pub with: Option<String>,
+}
+
+// The options of a struct's or enum's `#[xdr(...)]` attribute.
+struct ContainerAttrs {
+    /// The path of the library, `::xdr_rs_serialize` unless re-exported.
+    krate: syn::Path,
+    /// Set by `transparent`, to encode a struct as its only field.
+    transparent: Option<syn::Path>,
+}
+
+// The options of a field's `#[xdr(...)]` attribute.
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    default: bool,
+    rename: Option<String>,
+    with: Option<String>,
 }
 
 #[derive(Debug)]
 struct Enum {
     pub name: proc_macro2::Ident,
-    pub e_type: Option<proc_macro2::Ident>,
+    pub e_type: Option<proc_macro2::TokenStream>,
     pub unit: bool,
-    pub index: i32,
+    /// The discriminant as a Rust literal or path, written out as is and
+    /// matched against when reading.
+    pub discriminant: String,
+    /// Captures every discriminant without its own variant, holding the
+    /// discriminant followed by the body, if any.
+    pub default: bool,
+    /// The fields of a variant with several or named fields, which are
+    /// encoded in order like a struct's members.
+    pub members: Option<Vec<Member>>,
+    /// Whether those fields are unnamed, making the JSON `data` an array.
+    pub tuple: bool,
 }
 
-fn get_meta_items(attr: &syn::Attribute, ident: &str) -> Option<Vec<syn::NestedMeta>> {
-    if attr.path.segments.len() == 1 && attr.path.is_ident(ident) {
-        match attr.parse_meta() {
-            Ok(List(ref meta)) => Some(meta.nested.iter().cloned().collect()),
-            _ => None,
+/// The type a union switches on.
+#[derive(Debug, Clone, PartialEq)]
+enum Discriminant {
+    Int,
+    Unsigned,
+    Bool,
+    /// A derived enum, by its path.
+    Enum(String),
+}
+
+impl Discriminant {
+    fn rust_type(&self) -> &str {
+        match self {
+            Discriminant::Int => "i32",
+            Discriminant::Unsigned => "u32",
+            Discriminant::Bool => "bool",
+            Discriminant::Enum(path) => path,
         }
-    } else {
-        None
     }
 }
 
-fn get_array_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
-    get_meta_items(attr, "array")
+fn get_meta_items(attrs: &[syn::Attribute], ident: &str) -> syn::Result<Vec<syn::NestedMeta>> {
+    let mut items = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident(ident)) {
+        match attr.parse_meta()? {
+            List(meta) => items.extend(meta.nested),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    format!("expected `#[{}(...)]`", ident),
+                ))
+            }
+        }
+    }
+    Ok(items)
+}
+
+fn get_array_meta_items(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::NestedMeta>> {
+    get_meta_items(attrs, "array")
+}
+
+fn get_xdr_meta_items(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::NestedMeta>> {
+    get_meta_items(attrs, "xdr")
+}
+
+fn get_discriminant_meta_items(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::NestedMeta>> {
+    get_meta_items(attrs, "discriminant")
 }
 
-fn get_discriminant_meta_items(attr: &syn::Attribute) -> Option<Vec<syn::NestedMeta>> {
-    get_meta_items(attr, "discriminant")
+fn get_discriminant_value(expr: &syn::Expr) -> syn::Result<i32> {
+    let value = get_literal_value(expr)?;
+    i32::try_from(value).map_err(|_| int_range_error(expr))
 }
 
-fn get_enums(data: &syn::DataEnum) -> Result<Vec<Enum>, ()> {
+// Negation is applied before the range check, so that `-2147483648` is
+// accepted.
+fn get_literal_value(expr: &syn::Expr) -> syn::Result<i64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(ref i_val),
+            ..
+        }) => i_val
+            .base10_parse::<i64>()
+            .map_err(|_| int_range_error(expr)),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            ref expr,
+            ..
+        }) => get_literal_value(expr).map(|val| -val),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "discriminant must be an integer literal",
+        )),
+    }
+}
+
+fn int_range_error<T: ToTokens>(tokens: T) -> syn::Error {
+    syn::Error::new_spanned(
+        tokens,
+        "discriminant must be in the range of an `int`, -2147483648 to 2147483647",
+    )
+}
+
+fn get_array_size(lit: &syn::Lit) -> syn::Result<u32> {
+    match lit {
+        syn::Lit::Int(ref val) => val.base10_parse::<u32>(),
+        _ => Err(syn::Error::new_spanned(
+            lit,
+            "expected an integer array size",
+        )),
+    }
+}
+
+// Raw identifiers such as `r#type` keep their plain name in JSON and in
+// error paths.
+fn field_key(name: &proc_macro2::Ident) -> String {
+    name.to_string().trim_start_matches("r#").to_string()
+}
+
+// A parsed `#[discriminant(...)]`: the switch type, the discriminant as Rust
+// tokens, and the attribute it came from.
+type VariantDiscriminant = (Discriminant, String, syn::MetaNameValue);
+
+// Reads a variant's `#[discriminant(...)]`, which gives its discriminant as
+// `value = "-1"`, `unsigned = "1"`, `bool = "true"` or `enum = "Kind::Name"`,
+// or marks it as the `default`.
+fn get_variant_discriminant(
+    variant: &syn::Variant,
+) -> syn::Result<(Option<VariantDiscriminant>, bool)> {
+    let mut discriminant = None;
+    let mut default = false;
+    for meta_item in get_discriminant_meta_items(&variant.attrs)? {
+        let m = match meta_item {
+            Meta(syn::Meta::Path(ref p)) if p.is_ident("default") => {
+                default = true;
+                continue;
+            }
+            Meta(NameValue(m)) => m,
+            meta_item => {
+                return Err(syn::Error::new_spanned(
+                    meta_item,
+                    "expected `value = \"...\"` or `default`",
+                ))
+            }
+        };
+        let val = match m.lit {
+            syn::Lit::Str(ref val) => val.value(),
+            ref lit => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "expected a string, e.g. `value = \"1\"`",
+                ))
+            }
+        };
+        let invalid =
+            |expected: &str| syn::Error::new_spanned(&m.lit, format!("expected {}", expected));
+        let parsed = if m.path.is_ident("value") {
+            let n = val
+                .parse::<i64>()
+                .map_err(|_| invalid("an integer, e.g. \"1\""))?;
+            let n = i32::try_from(n).map_err(|_| {
+                invalid("an `int` discriminant, from \"-2147483648\" to \"2147483647\"")
+            })?;
+            (Discriminant::Int, format!("{}i32", n))
+        } else if m.path.is_ident("unsigned") {
+            let n = val
+                .parse::<i64>()
+                .map_err(|_| invalid("an unsigned integer, e.g. \"1\""))?;
+            let n = u32::try_from(n).map_err(|_| {
+                invalid("an `unsigned int` discriminant, from \"0\" to \"4294967295\"")
+            })?;
+            (Discriminant::Unsigned, format!("{}u32", n))
+        } else if m.path.is_ident("bool") {
+            let b = val
+                .parse::<bool>()
+                .map_err(|_| invalid("\"true\" or \"false\""))?;
+            (Discriminant::Bool, b.to_string())
+        } else if m.path.is_ident("enum") {
+            let path = syn::parse_str::<syn::Path>(&val)
+                .ok()
+                .filter(|path| path.segments.len() > 1)
+                .ok_or_else(|| invalid("an enum variant, e.g. \"Kind::Name\""))?;
+            let ty = syn::Path {
+                leading_colon: path.leading_colon,
+                segments: path
+                    .segments
+                    .iter()
+                    .take(path.segments.len() - 1)
+                    .cloned()
+                    .collect(),
+            };
+            (
+                Discriminant::Enum(ty.to_token_stream().to_string().replace(" ", "")),
+                path.to_token_stream().to_string(),
+            )
+        } else {
+            return Err(syn::Error::new_spanned(
+                &m.path,
+                "expected `value`, `unsigned`, `bool`, `enum` or `default`",
+            ));
+        };
+        if discriminant.is_some() {
+            return Err(syn::Error::new_spanned(m, "duplicate discriminant"));
+        }
+        discriminant = Some((parsed.0, parsed.1, m));
+    }
+    Ok((discriminant, default))
+}
+
+// All variants of a union must switch on the same type.
+fn check_discriminant<T: ToTokens>(
+    kind: &mut Option<Discriminant>,
+    found: Discriminant,
+    tokens: T,
+) -> syn::Result<()> {
+    match kind {
+        Some(kind) if *kind != found => Err(syn::Error::new_spanned(
+            tokens,
+            format!(
+                "expected a `{}` discriminant, like the other variants",
+                kind.rust_type()
+            ),
+        )),
+        Some(_) => Ok(()),
+        None => {
+            *kind = Some(found);
+            Ok(())
+        }
+    }
+}
+
+fn get_enums(data: &syn::DataEnum) -> syn::Result<(Vec<Enum>, Discriminant)> {
     let mut members = Vec::new();
+    let mut kind = None;
     let mut index: i32 = 0;
     for variant in &data.variants {
+        let (discriminant, default) = get_variant_discriminant(variant)?;
         match (&variant.fields, &variant.discriminant) {
-            (syn::Fields::Unit, Some(expr)) => match expr.1 {
-                syn::Expr::Lit(ref e_lit) => match e_lit.lit {
-                    syn::Lit::Int(ref i_val) => members.push(Enum {
-                        unit: true,
-                        index: i_val.base10_digits().parse::<i32>().unwrap(),
-                        name: variant.ident.clone(),
-                        e_type: None,
-                    }),
-                    _ => {}
-                },
-                _ => {}
-            },
-            (syn::Fields::Unnamed(un), None) => {
-                let mut member_index: i32 = index;
-                for meta_items in variant.attrs.iter().filter_map(get_discriminant_meta_items) {
-                    for meta_item in meta_items {
-                        match meta_item {
-                            Meta(NameValue(ref m)) if m.path.is_ident("value") => match m.lit {
-                                syn::Lit::Str(ref val) => {
-                                    member_index = val.value().parse::<i32>().unwrap();
-                                }
-                                _ => {}
-                            },
-                            _ => {}
-                        };
+            (syn::Fields::Unit, Some(expr)) if discriminant.is_none() && !default => {
+                check_discriminant(&mut kind, Discriminant::Int, variant)?;
+                members.push(Enum {
+                    unit: true,
+                    discriminant: format!("{}i32", get_discriminant_value(&expr.1)?),
+                    name: variant.ident.clone(),
+                    e_type: None,
+                    default: false,
+                    members: None,
+                    tuple: false,
+                })
+            }
+            (syn::Fields::Unit, _) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "enum variants need an explicit discriminant, e.g. `Name = 0`",
+                ))
+            }
+            (fields, None) => {
+                if default {
+                    if discriminant.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            variant,
+                            "the default variant cannot also have a discriminant value",
+                        ));
                     }
+                    members.push(get_default_variant(variant, &members)?);
+                    continue;
                 }
+                let pattern = match discriminant {
+                    Some((found, pattern, m)) => {
+                        check_discriminant(&mut kind, found, m)?;
+                        pattern
+                    }
+                    None => {
+                        check_discriminant(&mut kind, Discriminant::Int, variant)?;
+                        format!("{}i32", index)
+                    }
+                };
 
-                let types: Vec<_> = un
-                    .unnamed
-                    .iter()
-                    .filter_map(|f| match f.ty.clone() {
-                        syn::Type::Path(t_path) => Some(t_path.path.segments),
-                        _ => None,
-                    })
-                    .flatten()
-                    .map(|s| s.ident)
-                    .collect();
-                let ident = match types.len() {
-                    0 => None,
-                    1 => Some(types[0].clone()),
-                    _ => panic!("Cannot have a union with more than one type."),
+                let (e_type, fields) = match fields {
+                    syn::Fields::Unnamed(un) if un.unnamed.is_empty() => {
+                        return Err(syn::Error::new_spanned(
+                            un,
+                            "a void arm holds `()`, e.g. `Name(())`",
+                        ))
+                    }
+                    syn::Fields::Unnamed(un) if un.unnamed.len() <= 1 => {
+                        for field in un.unnamed.iter() {
+                            check_no_field_attrs(
+                                field,
+                                "a single-field variant's field cannot take `#[array]` or `#[xdr]`, wrap it in a newtype instead",
+                            )?;
+                        }
+                        let e_type = un.unnamed.first().and_then(|f| match f.ty {
+                            syn::Type::Tuple(ref tuple) if tuple.elems.is_empty() => None,
+                            ref ty => Some(ty.into_token_stream()),
+                        });
+                        (e_type, None)
+                    }
+                    fields => {
+                        let mut fields_members = get_members(fields)?;
+                        // The fields are bound by reference when matching.
+                        for member in fields_members.iter_mut() {
+                            member.value = format!("(*{})", binding_ident(member));
+                        }
+                        (None, Some(fields_members))
+                    }
                 };
                 members.push(Enum {
                     unit: false,
-                    index: member_index,
+                    discriminant: pattern,
                     name: variant.ident.clone(),
-                    e_type: ident,
+                    e_type,
+                    default: false,
+                    members: fields,
+                    tuple: is_tuple(&variant.fields),
                 });
                 index += 1;
             }
-            _ => {}
+            (_, Some(expr)) => return Err(syn::Error::new_spanned(
+                &expr.1,
+                "union variants take their discriminant from `#[discriminant(value = \"...\")]`",
+            )),
+        }
+    }
+
+    let kind = kind.unwrap_or(Discriminant::Int);
+    Ok((members, kind))
+}
+
+// A `#[discriminant(default)]` variant holds the discriminant it was read
+// with, then the arm's body unless the arm is void: `Other(i32)` or
+// `Other(i32, Body)`.
+fn get_default_variant(variant: &syn::Variant, members: &[Enum]) -> syn::Result<Enum> {
+    if members.iter().any(|m| m.default) {
+        return Err(syn::Error::new_spanned(
+            variant,
+            "only one variant can be the default",
+        ));
+    }
+    let fields = match variant.fields {
+        syn::Fields::Unnamed(ref un) => un,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "the default variant holds the discriminant and an optional body, e.g. `Other(i32, Body)`",
+            ))
         }
+    };
+    let types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
+    if types.is_empty() || types.len() > 2 {
+        return Err(syn::Error::new_spanned(
+            fields,
+            "the default variant holds the discriminant and an optional body, e.g. `Other(i32, Body)`",
+        ));
+    }
+    for field in fields.unnamed.iter() {
+        check_no_field_attrs(
+            field,
+            "the default variant's fields cannot take `#[array]` or `#[xdr]`, wrap the body in a newtype instead",
+        )?;
     }
-    Ok(members)
+    Ok(Enum {
+        unit: false,
+        discriminant: String::new(),
+        name: variant.ident.clone(),
+        e_type: types.get(1).map(|ty| ty.into_token_stream()),
+        default: true,
+        members: None,
+        tuple: false,
+    })
+}
+
+// The default variant's first field must be the discriminant's type. The
+// compiler compares the types, so aliases and qualified paths are accepted.
+fn get_default_discriminant_check(data: &syn::DataEnum) -> syn::Result<proc_macro2::TokenStream> {
+    let (enums, kind) = get_enums(data)?;
+    let field = enums
+        .iter()
+        .find(|enu| enu.default)
+        .and_then(|enu| data.variants.iter().find(|v| v.ident == enu.name))
+        .and_then(|variant| variant.fields.iter().next());
+    let field = match field {
+        Some(field) => field,
+        None => return Ok(quote!()),
+    };
+    let discriminant: proc_macro2::TokenStream = kind.rust_type().parse().unwrap();
+    let ty = &field.ty;
+    let span = ty.span();
+    Ok(quote_spanned! {span=>
+        const _: ::std::marker::PhantomData<#discriminant> = ::std::marker::PhantomData::<#ty>;
+    })
 }
 
 fn get_calls_enum_in_xdr(
     data: &syn::DataEnum,
     enum_name: &syn::Ident,
-) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let enums = get_enums(data)?;
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let (enums, _) = get_enums(data)?;
     let mut result = Vec::new();
-    for enu in enums.iter() {
-        match (&enu.name, enu.unit, enu.index, &enu.e_type) {
+    for enu in enums.iter().filter(|enu| !enu.default) {
+        if let Some(ref members) = enu.members {
+            let discriminant: proc_macro2::TokenStream = enu.discriminant.parse().unwrap();
+            let name = &enu.name;
+            let calls = get_calls_fields_in_xdr(members, false, false);
+            let build = get_build_in_xdr(members);
+            result.push(quote! {
+                #discriminant => {
+                    let mut read: u64 = 4;
+                    #(#calls)*
+                    Ok((#enum_name::#name { #(#build)* }, read))
+                },
+            });
+            continue;
+        }
+        match (&enu.name, enu.unit, &enu.discriminant, &enu.e_type) {
             (name, true, i, None) => {
                 result.push(
                     format!("{} => Ok(({}::{}, 4)),", i, enum_name, name)
@@ -138,9 +499,9 @@ fn get_calls_enum_in_xdr(
             (name, false, i, Some(typ)) => {
                 result.push(
                     format!(
-                        "{} => {{let result = {}::read_xdr(&buffer[4..])?; Ok(({}::{}(result.0), result.1 + 4))}},",
+                        "{} => {{let result = <{} as __xdr::de::XDRIn>::read_xdr_from(reader)?; Ok(({}::{}(result.0), result.1 + 4))}},",
                         i,
-                        typ.to_string().replace("<", "::<"),
+                        typ,
                         enum_name,
                         name
                     )
@@ -151,7 +512,7 @@ fn get_calls_enum_in_xdr(
             (name, false, i, None) => {
                 result.push(
                     format!(
-                        "{} => {{let result = <()>::read_xdr(&buffer[..])?; Ok(({}::{}(result.0), result.1 + 4))}},",
+                        "{} => {{let result = <()>::read_xdr_from(reader)?; Ok(({}::{}(result.0), result.1 + 4))}},",
                         i,
                         enum_name,
                         name
@@ -160,22 +521,52 @@ fn get_calls_enum_in_xdr(
                     .unwrap(),
                 );
             }
-            _ => {
-                return Err(());
-            }
+            _ => unreachable!("variants are either unit or have a single field"),
         }
     }
+    let fallback = match enums.iter().find(|enu| enu.default) {
+        Some(Enum {
+            name,
+            e_type: Some(typ),
+            ..
+        }) => format!(
+            "_ => {{let result = <{} as __xdr::de::XDRIn>::read_xdr_from(reader)?; Ok(({}::{}(enum_val, result.0), result.1 + 4))}},",
+            typ,
+            enum_name,
+            name
+        ),
+        Some(Enum { name, .. }) => format!("_ => Ok(({}::{}(enum_val), 4)),", enum_name, name),
+        None => "_ => Err(__xdr::error::Error::invalid_enum_value().at_offset(reader.position() - 4)),"
+            .to_string(),
+    };
+    result.push(fallback.parse().unwrap());
     Ok(result)
 }
 
 fn get_calls_enum_in_json(
     data: &syn::DataEnum,
     enum_name: &syn::Ident,
-) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let enums = get_enums(data)?;
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let (enums, _) = get_enums(data)?;
     let mut result = Vec::new();
-    for enu in enums.iter() {
-        match (&enu.name, enu.unit, enu.index, &enu.e_type) {
+    for enu in enums.iter().filter(|enu| !enu.default) {
+        if let Some(ref members) = enu.members {
+            let discriminant: proc_macro2::TokenStream = enu.discriminant.parse().unwrap();
+            let name = &enu.name;
+            let container = json_container(enu.tuple);
+            let calls = get_calls_fields_in_json(members, enu.tuple, false);
+            let build = get_build_in_json(members);
+            result.push(quote! {
+                #discriminant => {
+                    let jval = enum_val.clone();
+                    #container
+                    #(#calls)*
+                    Ok(#enum_name::#name { #(#build)* })
+                },
+            });
+            continue;
+        }
+        match (&enu.name, enu.unit, &enu.discriminant, &enu.e_type) {
             (name, true, i, None) => {
                 result.push(
                     format!("{} => Ok({}::{}),", i, enum_name, name)
@@ -186,9 +577,9 @@ fn get_calls_enum_in_json(
             (name, false, i, Some(typ)) => {
                 result.push(
                     format!(
-                        "{} => {{let result = {}::read_json(enum_val.clone())?; Ok({}::{}(result))}},",
+                        "{} => {{let result = <{} as __xdr::de::XDRIn>::read_json(enum_val.clone())?; Ok({}::{}(result))}},",
                         i,
-                        typ.to_string().replace("<", "::<"),
+                        typ,
                         enum_name,
                         name
                     )
@@ -208,22 +599,85 @@ fn get_calls_enum_in_json(
                     .unwrap(),
                 );
             }
-            _ => {
-                return Err(());
-            }
+            _ => unreachable!("variants are either unit or have a single field"),
         }
     }
+    let fallback = match enums.iter().find(|enu| enu.default) {
+        Some(Enum {
+            name,
+            e_type: Some(typ),
+            ..
+        }) => format!(
+            "_ => {{let result = <{} as __xdr::de::XDRIn>::read_json(enum_val.clone())?; Ok({}::{}(enum_index, result))}},",
+            typ,
+            enum_name,
+            name
+        ),
+        Some(Enum { name, .. }) => format!(
+            "_ => {{<()>::read_json(enum_val.clone())?; Ok({}::{}(enum_index))}},",
+            enum_name, name
+        ),
+        None => "_ => Err(__xdr::error::Error::invalid_enum_value()),".to_string(),
+    };
+    result.push(fallback.parse().unwrap());
     Ok(result)
 }
 
-fn get_calls_enum_out_xdr(data: &syn::DataEnum) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let enums = get_enums(data)?;
+// Binds each field of a variant by reference, e.g.
+// `Pay { to: ref __to, amount: ref __amount }`, under names that cannot
+// shadow the generated code's own variables.
+fn variant_pattern(name: &syn::Ident, members: &[Member]) -> proc_macro2::TokenStream {
+    let bindings = members.iter().map(|m| {
+        let access: proc_macro2::TokenStream = m.access.parse().unwrap();
+        let binding = binding_ident(m);
+        quote!(#access: ref #binding)
+    });
+    quote!(#name { #(#bindings),* })
+}
+
+fn write_json_literal(literal: &str) -> proc_macro2::TokenStream {
+    let len = literal.len() as u64;
+    quote!(out.write_all(#literal.as_bytes())?; written += #len;)
+}
+
+fn get_calls_enum_out_xdr(data: &syn::DataEnum) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let (enums, _) = get_enums(data)?;
     let mut result = Vec::new();
     for enu in enums.iter() {
-        match (&enu.name, enu.unit, enu.index) {
+        if let Some(ref members) = enu.members {
+            let pattern = variant_pattern(&enu.name, members);
+            let discriminant: proc_macro2::TokenStream = enu.discriminant.parse().unwrap();
+            let calls = get_calls_fields_out_xdr(members);
+            result.push(quote! {
+                #pattern => {
+                    let mut written = 0;
+                    written += (#discriminant).write_xdr(out)?;
+                    #(#calls)*
+                    Ok(written)
+                },
+            });
+            continue;
+        }
+        match (&enu.name, enu.unit, &enu.discriminant) {
+            (name, _, _) if enu.default => {
+                let body = match enu.e_type {
+                    Some(_) => "written += val.write_xdr(out)?;",
+                    None => "",
+                };
+                result.push(
+                    format!(
+                        "{}(ref discriminant{}) => {{let mut written = 0; written += discriminant.write_xdr(out)?; {} Ok(written)}},",
+                        name,
+                        if enu.e_type.is_some() { ", ref val" } else { "" },
+                        body
+                    )
+                    .parse()
+                    .unwrap(),
+                );
+            }
             (name, true, i) => {
                 result.push(
-                    format!("{} => ({} as i32).write_xdr(out),", name, i)
+                    format!("{} => ({}).write_xdr(out),", name, i)
                         .parse()
                         .unwrap(),
                 );
@@ -231,7 +685,7 @@ fn get_calls_enum_out_xdr(data: &syn::DataEnum) -> Result<Vec<proc_macro2::Token
             (name, false, i) => {
                 result.push(
                     format!(
-                        "{}(ref val) => {{let mut written = 0; written += ({} as i32).write_xdr(out)?; written += val.write_xdr(out)?; Ok(written)}},",
+                        "{}(ref val) => {{let mut written = 0; written += ({}).write_xdr(out)?; written += val.write_xdr(out)?; Ok(written)}},",
                         name, i
                     )
                     .parse()
@@ -243,14 +697,108 @@ fn get_calls_enum_out_xdr(data: &syn::DataEnum) -> Result<Vec<proc_macro2::Token
     Ok(result)
 }
 
-fn get_calls_enum_out_json(data: &syn::DataEnum) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let enums = get_enums(data)?;
+// The arms of `xdr_size`, and the union's size if every arm has the same
+// constant size.
+fn get_calls_enum_size(
+    data: &syn::DataEnum,
+    name: &syn::Ident,
+) -> syn::Result<(Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream)> {
+    let (enums, _) = get_enums(data)?;
+    let mut arms = Vec::new();
+    let mut sizes = Vec::new();
+    for enu in enums.iter() {
+        let variant = &enu.name;
+        let discriminant: proc_macro2::TokenStream = enu.discriminant.parse().unwrap();
+        if let Some(ref members) = enu.members {
+            let pattern = variant_pattern(variant, members);
+            let calls = get_calls_fields_size(members);
+            arms.push(quote! {
+                #name::#pattern => {
+                    let mut size = (#discriminant).xdr_size();
+                    #(#calls)*
+                    size
+                },
+            });
+            sizes.push(get_const_size(&[4], members));
+            continue;
+        }
+        let body_size = enu.e_type.as_ref().map(|ty| {
+            let span = ty.span();
+            quote_spanned!(span=> <#ty as __xdr::ser::XDROut>::XDR_SIZE)
+        });
+        let (arm, size) = match (enu.default, &body_size) {
+            (true, Some(_)) => (
+                quote!(#name::#variant(ref discriminant, ref val) => discriminant.xdr_size() + val.xdr_size(),),
+                quote!(__xdr::ser::sum_xdr_sizes(&[::std::option::Option::Some(4), #body_size])),
+            ),
+            (true, None) => (
+                quote!(#name::#variant(ref discriminant) => discriminant.xdr_size(),),
+                quote!(::std::option::Option::Some(4)),
+            ),
+            (false, _) if enu.unit => (
+                quote!(#name::#variant => (#discriminant).xdr_size(),),
+                quote!(::std::option::Option::Some(4)),
+            ),
+            (false, _) => (
+                quote!(#name::#variant(ref val) => (#discriminant).xdr_size() + val.xdr_size(),),
+                quote!(__xdr::ser::sum_xdr_sizes(&[::std::option::Option::Some(4), #body_size])),
+            ),
+        };
+        arms.push(arm);
+        sizes.push(size);
+    }
+    Ok((arms, quote!(__xdr::ser::same_xdr_size(&[#(#sizes),*]))))
+}
+
+fn get_calls_enum_out_json(data: &syn::DataEnum) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let (enums, _) = get_enums(data)?;
     let mut result = Vec::new();
     for enu in enums.iter() {
-        match (&enu.name, enu.unit, enu.index) {
+        if let Some(ref members) = enu.members {
+            let pattern = variant_pattern(&enu.name, members);
+            let discriminant: proc_macro2::TokenStream = enu.discriminant.parse().unwrap();
+            let calls = get_calls_fields_out_json(members, enu.tuple, false);
+            let (open, data, close) = (
+                write_json_literal("{\"type\":"),
+                write_json_literal(",\"data\":"),
+                write_json_literal("}"),
+            );
+            result.push(quote! {
+                #pattern => {
+                    let mut written = 0;
+                    #open
+                    written += (#discriminant).write_json(out)?;
+                    #data
+                    #(#calls)*
+                    #close
+                    Ok(written)
+                },
+            });
+            continue;
+        }
+        match (&enu.name, enu.unit, &enu.discriminant) {
+            (name, _, _) if enu.default => {
+                let (binding, body) = match enu.e_type {
+                    Some(_) => (", ref val", "written += val.write_json(out)?;"),
+                    None => ("", "written += ().write_json(out)?;"),
+                };
+                result.push(
+                    format!(
+                        "{}(ref discriminant{}) => {{let mut written = 0; {} written += discriminant.write_json(out)?; {} {} {} Ok(written)}},",
+                        name,
+                        binding,
+                        write_json_literal("{\"type\":"),
+                        write_json_literal(",\"data\":"),
+                        body,
+                        write_json_literal("}")
+                    )
+                    .parse()
+                    .unwrap(),
+                );
+            }
             (name, true, i) => {
                 result.push(
-                    format!("{} => ({} as i32).write_json(out),", name, i)
+                    format!("{} => ({}).write_json(out),", name, i)
                         .parse()
                         .unwrap(),
                 );
@@ -258,8 +806,12 @@ fn get_calls_enum_out_json(data: &syn::DataEnum) -> Result<Vec<proc_macro2::Toke
             (name, false, i) => {
                 result.push(
                     format!(
-                        r#"{}(ref val) => {{let mut written = 0; written += out.write("{{\"type\":".as_bytes())? as u64;  written += ({} as i32).write_json(out)?; written += out.write(",\"data\":".as_bytes())? as u64; written +=  val.write_json(out)?; written += out.write("}}".as_bytes())? as u64; Ok(written)}},"#,
-                        name, i
+                        "{}(ref val) => {{let mut written = 0; {} written += ({}).write_json(out)?; {} written += val.write_json(out)?; {} Ok(written)}},",
+                        name,
+                        write_json_literal("{\"type\":"),
+                        i,
+                        write_json_literal(",\"data\":"),
+                        write_json_literal("}")
                     )
                     .parse()
                     .unwrap(),
@@ -270,373 +822,680 @@ fn get_calls_enum_out_json(data: &syn::DataEnum) -> Result<Vec<proc_macro2::Toke
     Ok(result)
 }
 
-fn get_members(data: &syn::DataStruct) -> Result<Vec<Member>, ()> {
+fn get_struct_members(data: &syn::DataStruct) -> syn::Result<Vec<Member>> {
     match data.fields {
-        syn::Fields::Named(ref named) => {
+        syn::Fields::Unit => Err(syn::Error::new_spanned(
+            data.struct_token,
+            "unit structs are not supported",
+        )),
+        ref fields => get_members(fields),
+    }
+}
+
+fn get_members(fields: &syn::Fields) -> syn::Result<Vec<Member>> {
+    match fields {
+        syn::Fields::Named(syn::FieldsNamed {
+            named: ref fields, ..
+        })
+        | syn::Fields::Unnamed(syn::FieldsUnnamed {
+            unnamed: ref fields,
+            ..
+        }) => {
             let mut members = Vec::new();
-            for field in named.named.iter() {
-                let mut fixed: u32 = 0;
-                let mut var: u32 = 0;
-                for meta_items in field.attrs.iter().filter_map(get_array_meta_items) {
-                    for meta_item in meta_items {
-                        match meta_item {
-                            Meta(NameValue(ref m)) if m.path.is_ident("fixed") => match m.lit {
-                                syn::Lit::Int(ref val) => {
-                                    fixed = val.base10_digits().parse::<u32>().unwrap();
-                                }
-                                _ => {}
-                            },
-                            Meta(NameValue(ref m)) if m.path.is_ident("var") => match m.lit {
-                                syn::Lit::Int(ref val) => {
-                                    var = val.base10_digits().parse::<u32>().unwrap();
-                                }
-                                _ => {}
-                            },
-                            _ => {}
-                        };
-                    }
+            // Skipped fields take no place in a tuple's JSON array.
+            let mut position = 0;
+            for (index, field) in fields.iter().enumerate() {
+                let (fixed, var) = get_array_sizes(field)?;
+                let attrs = get_field_attrs(field)?;
+                if attrs.with.is_some() && (fixed.is_some() || var.is_some()) {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "`with` cannot be combined with `#[array]`",
+                    ));
                 }
 
+                let (name, access, key) = match field.ident {
+                    Some(ref ident) => (
+                        ident.clone(),
+                        ident.to_string(),
+                        attrs.rename.unwrap_or_else(|| field_key(ident)),
+                    ),
+                    None => (
+                        proc_macro2::Ident::new(
+                            &format!("field{}", index),
+                            proc_macro2::Span::call_site(),
+                        ),
+                        index.to_string(),
+                        position.to_string(),
+                    ),
+                };
+                if !attrs.skip {
+                    position += 1;
+                }
                 members.push(Member {
-                    name: field.ident.clone().unwrap(),
-                    fixed: fixed,
-                    var: var,
+                    value: format!("self.{}", access),
+                    name,
+                    access,
+                    key,
+                    fixed: fixed.unwrap_or(0),
+                    var: var.unwrap_or(0),
                     v_type: field.ty.clone().into_token_stream(),
+                    skip: attrs.skip,
+                    default: attrs.default,
+                    with: attrs.with,
                 });
             }
             Ok(members)
         }
-        _ => Err(()),
+        syn::Fields::Unit => Ok(Vec::new()),
     }
 }
 
-fn member_to_json_dict(mem: &Member, skip_name: bool) -> Result<String, ()> {
-    let mut lines: Vec<String> = Vec::new();
-    if !skip_name {
-        let name_str = format!(
-            r#"written += out.write("\"{}\":".as_bytes())? as u64;"#,
-            mem.name
-        );
-        lines.push(name_str);
+// Reads the `fixed` and `var` sizes of a field's `#[array]` attribute. Whether
+// they suit the field's type is left to the `XDRFixedOut` and `XDRVarOut`
+// bounds of the generated code.
+fn get_array_sizes(field: &syn::Field) -> syn::Result<(Option<u32>, Option<u32>)> {
+    let mut fixed = None;
+    let mut var = None;
+    for meta_item in get_array_meta_items(&field.attrs)? {
+        let (size, m) = match meta_item {
+            Meta(NameValue(ref m)) if m.path.is_ident("fixed") => (&mut fixed, m),
+            Meta(NameValue(ref m)) if m.path.is_ident("var") => (&mut var, m),
+            meta_item => {
+                return Err(syn::Error::new_spanned(
+                    meta_item,
+                    "expected `fixed = N` or `var = N`",
+                ))
+            }
+        };
+        if size.is_some() {
+            return Err(syn::Error::new_spanned(m, "duplicate array size"));
+        }
+        *size = Some(get_array_size(&m.lit)?);
     }
 
-    let out = match (
-        &mem.name,
-        mem.fixed,
-        mem.var,
-        mem.v_type.to_string() == "String",
-        mem.v_type.to_string().replace(" ", "") == "Vec<u8>",
-    ) {
-        (name, 0, 0, false, false) => format!("written += self.{}.write_json(out)?;", name),
-        (name, fixed, 0, false, false) => format!(
-            "written += write_fixed_array_json(&self.{}, {}, out)?;",
-            name, fixed
-        ),
-        (name, fixed, 0, false, true) => format!(
-            "written += write_fixed_opaque_json(&self.{}, {}, out)?;",
-            name, fixed
-        ),
-        (name, 0, var, false, true) => format!(
-            "written += write_var_opaque_json(&self.{}, {}, out)?;",
-            name, var
-        ),
-        (name, 0, var, true, false) => format!(
-            "written += write_var_string_json(self.{}.clone(), {}, out)?;",
-            name, var
-        ),
-        (name, 0, var, false, false) => format!(
-            "written += write_var_array_json(&self.{}, {}, out)?;",
-            name, var
-        ),
-        _ => "".to_string(),
+    match (fixed, var) {
+        (Some(_), Some(_)) => Err(syn::Error::new_spanned(
+            field,
+            "an array cannot be both `fixed` and `var`",
+        )),
+        sizes => Ok(sizes),
+    }
+}
+
+// Reads a field's `#[xdr(...)]`, which may `skip` it, `rename` its JSON key,
+// give a `default` for a missing key, or encode it `with` a module's
+// `write_xdr`, `write_json`, `read_xdr_from` and `read_json` functions.
+fn get_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for meta_item in get_xdr_meta_items(&field.attrs)? {
+        match meta_item {
+            Meta(syn::Meta::Path(ref p)) if p.is_ident("skip") => attrs.skip = true,
+            Meta(syn::Meta::Path(ref p)) if p.is_ident("default") => attrs.default = true,
+            Meta(NameValue(ref m)) if m.path.is_ident("rename") => {
+                if field.ident.is_none() {
+                    return Err(syn::Error::new_spanned(
+                        m,
+                        "only named fields can be renamed",
+                    ));
+                }
+                attrs.rename = Some(get_lit_str(&m.lit)?.value());
+            }
+            Meta(NameValue(ref m)) if m.path.is_ident("with") => {
+                let val = get_lit_str(&m.lit)?;
+                let path: syn::Path = val.parse().map_err(|_| {
+                    syn::Error::new_spanned(
+                        val,
+                        "expected a module path, e.g. `with = \"hex_key\"`",
+                    )
+                })?;
+                attrs.with = Some(path.into_token_stream().to_string());
+            }
+            meta_item => {
+                return Err(syn::Error::new_spanned(
+                    meta_item,
+                    "expected `skip`, `default`, `rename = \"...\"` or `with = \"...\"`",
+                ))
+            }
+        }
+    }
+    Ok(attrs)
+}
+
+// Fields encoded as a bare type, without a `Member`, have nowhere to apply
+// `#[array]` or `#[xdr]`, so those attributes are rejected rather than
+// ignored.
+fn check_no_field_attrs(field: &syn::Field, message: &str) -> syn::Result<()> {
+    match field
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("array") || attr.path.is_ident("xdr"))
+    {
+        Some(attr) => Err(syn::Error::new_spanned(attr, message)),
+        None => Ok(()),
+    }
+}
+
+fn get_lit_str(lit: &syn::Lit) -> syn::Result<&syn::LitStr> {
+    match lit {
+        syn::Lit::Str(ref val) => Ok(val),
+        _ => Err(syn::Error::new_spanned(lit, "expected a string")),
+    }
+}
+
+fn is_tuple(fields: &syn::Fields) -> bool {
+    matches!(fields, syn::Fields::Unnamed(_))
+}
+
+// Newtypes, and structs marked `#[xdr(transparent)]`, are typedefs of their
+// member and are encoded as just that member.
+fn is_transparent(
+    attrs: &ContainerAttrs,
+    data: &syn::DataStruct,
+    members: &[Member],
+) -> syn::Result<bool> {
+    let mut encoded = members.iter().filter(|m| !m.skip);
+    match (encoded.next(), encoded.next(), &attrs.transparent) {
+        (Some(_), None, transparent) => Ok(is_tuple(&data.fields) || transparent.is_some()),
+        (_, _, Some(path)) => Err(syn::Error::new_spanned(
+            path,
+            "a transparent struct needs exactly one field that is not skipped",
+        )),
+        _ => Ok(false),
+    }
+}
+
+fn member_to_json_dict(mem: &Member, skip_name: bool) -> proc_macro2::TokenStream {
+    let key = if skip_name {
+        quote!()
+    } else {
+        write_json_literal(&format!("{:?}:", mem.key))
     };
-    lines.push(out);
-    Ok(lines.join("\n"))
+    let write = member_write(mem, true);
+    quote!(#key #write)
 }
 
-fn get_calls_struct_out_json(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    let mut lines: Vec<String> = Vec::new();
-    if members.len() == 1 && members[0].name == "t".to_string() {
-        lines.push(member_to_json_dict(&members[0], true)?);
-        return Ok(vec![lines.join("\n").parse().unwrap()]);
+// Writes a member with its `with` module, or else through the trait that its
+// `#[array]` attribute calls for, so that any spelling of the type works.
+// Trait errors point at the member's type.
+fn member_write(mem: &Member, json: bool) -> proc_macro2::TokenStream {
+    let value: proc_macro2::TokenStream = mem.value.parse().unwrap();
+    let ty = &mem.v_type;
+    let span = ty.span();
+    let (plain, fixed, var) = if json {
+        (
+            quote!(write_json),
+            quote!(write_fixed_json),
+            quote!(write_var_json),
+        )
+    } else {
+        (
+            quote!(write_xdr),
+            quote!(write_fixed_xdr),
+            quote!(write_var_xdr),
+        )
+    };
+    let call = match (&mem.with, mem.fixed, mem.var) {
+        (Some(with), _, _) => {
+            let with: proc_macro2::TokenStream = with.parse().unwrap();
+            quote!(#with::#plain(&#value, out))
+        }
+        (None, 0, 0) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDROut>::#plain);
+            quote!(#path(&#value, out))
+        }
+        (None, size, 0) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDRFixedOut>::#fixed);
+            quote!(#path(&#value, #size, out))
+        }
+        (None, 0, max) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDRVarOut>::#var);
+            quote!(#path(&#value, #max, out))
+        }
+        _ => unreachable!("array sizes are checked by get_array_sizes"),
+    };
+    quote!(written += #call?;)
+}
+
+// The encoded size of a member, computed like `member_write` writes it.
+fn member_size(mem: &Member) -> proc_macro2::TokenStream {
+    let value: proc_macro2::TokenStream = mem.value.parse().unwrap();
+    let ty = &mem.v_type;
+    let span = ty.span();
+    match (&mem.with, mem.fixed, mem.var) {
+        (Some(with), _, _) => {
+            let with: proc_macro2::TokenStream = with.parse().unwrap();
+            quote!(#with::xdr_size(&#value))
+        }
+        (None, 0, 0) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDROut>::xdr_size);
+            quote!(#path(&#value))
+        }
+        (None, _, 0) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDRFixedOut>::fixed_xdr_size);
+            quote!(#path(&#value))
+        }
+        (None, 0, _) => {
+            let path = quote_spanned!(span=> <#ty as __xdr::ser::XDRVarOut>::var_xdr_size);
+            quote!(#path(&#value))
+        }
+        _ => unreachable!("array sizes are checked by get_array_sizes"),
     }
-    lines.push(r#"written += out.write("{".as_bytes())? as u64;"#.to_string());
-    if members.len() == 0 {
-        lines.push(r#"written += out.write("}".as_bytes())? as u64;"#.to_string());
-        return Ok(vec![lines.join("\n").parse().unwrap()]);
+}
+
+// The member's size if it is the same for every value, as an
+// `Option<usize>` constant.
+fn member_const_size(mem: &Member) -> proc_macro2::TokenStream {
+    let ty = &mem.v_type;
+    let span = ty.span();
+    match (&mem.with, mem.fixed, mem.var) {
+        (Some(_), _, _) => quote!(::std::option::Option::None),
+        (None, 0, 0) => quote_spanned!(span=> <#ty as __xdr::ser::XDROut>::XDR_SIZE),
+        (None, size, 0) => {
+            let element = quote_spanned!(span=> <#ty as __xdr::ser::XDRFixedOut>::XDR_ELEMENT_SIZE);
+            quote!(__xdr::ser::fixed_xdr_size(#element, #size))
+        }
+        (None, 0, _) => quote!(::std::option::Option::None),
+        _ => unreachable!("array sizes are checked by get_array_sizes"),
     }
-    let mem = members[0].clone();
-    lines.push(member_to_json_dict(&mem, false)?);
-    if members.len() == 1 {
-        lines.push(r#"written += out.write("}".as_bytes())? as u64;"#.to_string());
-        return Ok(vec![lines.join("\n").parse().unwrap()]);
+}
+
+// Where a member is read from.
+enum Source {
+    Xdr,
+    /// XDR in a buffer that borrowed members may point into.
+    BorrowedXdr,
+    /// The member's JSON value.
+    Json(proc_macro2::TokenStream),
+}
+
+// The lifetime of the buffer in derived `XDRInBorrowed` impls.
+fn borrowed_lifetime() -> syn::Lifetime {
+    syn::Lifetime::new("'__xdr", proc_macro2::Span::call_site())
+}
+
+// Reads a member like `member_write` writes it.
+fn member_read(mem: &Member, source: Source) -> proc_macro2::TokenStream {
+    if let Some(with) = &mem.with {
+        let with: proc_macro2::TokenStream = with.parse().unwrap();
+        return match source {
+            Source::Json(value) => quote!(#with::read_json(#value)),
+            _ => quote!(#with::read_xdr_from(reader)),
+        };
+    }
+    let ty = &mem.v_type;
+    let span = ty.span();
+    let (size, trait_name, method) = match (mem.fixed, mem.var) {
+        (0, 0) => (quote!(), "XDRIn", "read"),
+        (size, 0) => (quote!(#size,), "XDRFixedIn", "read_fixed"),
+        (0, max) => (quote!(#max,), "XDRVarIn", "read_var"),
+        _ => unreachable!("array sizes are checked by get_array_sizes"),
+    };
+    let call_site = proc_macro2::Span::call_site();
+    match source {
+        Source::Xdr => {
+            let trait_name = format_ident!("{}", trait_name, span = call_site);
+            let method = format_ident!("{}_xdr_from", method, span = call_site);
+            let path = quote_spanned!(span=> <#ty as __xdr::de::#trait_name>::#method);
+            quote!(#path(#size reader))
+        }
+        Source::BorrowedXdr => {
+            let trait_name = format_ident!("{}Borrowed", trait_name, span = call_site);
+            let method = format_ident!("{}_xdr_borrowed_from", method, span = call_site);
+            let lifetime = borrowed_lifetime();
+            let path = quote_spanned!(span=> <#ty as __xdr::de::#trait_name<#lifetime>>::#method);
+            quote!(#path(#size reader))
+        }
+        Source::Json(value) => {
+            let trait_name = format_ident!("{}", trait_name, span = call_site);
+            let method = format_ident!("{}_json", method, span = call_site);
+            let path = quote_spanned!(span=> <#ty as __xdr::de::#trait_name>::#method);
+            quote!(#path(#size #value))
+        }
     }
+}
+
+// The variable a member is decoded into, e.g. `amount_result`. Generated
+// names take the call site's span so that lints do not blame the field.
+fn result_ident(mem: &Member) -> proc_macro2::Ident {
+    format_ident!("{}_result", mem.name, span = proc_macro2::Span::call_site())
+}
+
+// The variable a union variant's field is bound to, e.g. `__amount`.
+fn binding_ident(mem: &Member) -> proc_macro2::Ident {
+    format_ident!("__{}", mem.name, span = proc_macro2::Span::call_site())
+}
 
-    for mem in members[1..].iter() {
-        lines.push(r#"written += out.write(",".as_bytes())? as u64;"#.to_string());
-        lines.push(member_to_json_dict(mem, false)?);
+fn get_calls_fields_out_json(
+    members: &[Member],
+    tuple: bool,
+    transparent: bool,
+) -> Vec<proc_macro2::TokenStream> {
+    let members: Vec<&Member> = members.iter().filter(|m| !m.skip).collect();
+    if transparent {
+        return vec![member_to_json_dict(members[0], true)];
     }
-    lines.push(r#"written += out.write("}".as_bytes())? as u64;"#.to_string());
-    Ok(vec![lines.join("\n").parse().unwrap()])
+    let (open, close) = if tuple { ("[", "]") } else { ("{", "}") };
+    let mut lines = vec![write_json_literal(open)];
+    for (index, mem) in members.iter().enumerate() {
+        if index > 0 {
+            lines.push(write_json_literal(","));
+        }
+        lines.push(member_to_json_dict(mem, tuple));
+    }
+    lines.push(write_json_literal(close));
+    lines
 }
 
-fn get_calls_struct_out_xdr(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    Ok(members
+fn get_calls_fields_size(members: &[Member]) -> Vec<proc_macro2::TokenStream> {
+    members
         .iter()
+        .filter(|i| !i.skip)
         .map(|i| {
-            match (
-                &i.name,
-                i.fixed,
-                i.var,
-                i.v_type.to_string() == "String",
-                i.v_type.to_string().replace(" ", "") == "Vec<u8>",
-            ) {
-                (name, 0, 0, false, false) => format!("written += self.{}.write_xdr(out)?;", name)
-                    .parse()
-                    .unwrap(),
-                (name, fixed, 0, false, false) => format!(
-                    "written += write_fixed_array(&self.{}, {}, out)?;",
-                    name, fixed
-                )
-                .parse()
-                .unwrap(),
-                (name, fixed, 0, false, true) => format!(
-                    "written += write_fixed_opaque(&self.{}, {}, out)?;",
-                    name, fixed
-                )
-                .parse()
-                .unwrap(),
-                (name, 0, var, false, true) => format!(
-                    "written += write_var_opaque(&self.{}, {}, out)?;",
-                    name, var
-                )
-                .parse()
-                .unwrap(),
-                (name, 0, var, true, false) => format!(
-                    "written += write_var_string(self.{}.clone(), {}, out)?;",
-                    name, var
-                )
-                .parse()
-                .unwrap(),
-                (name, 0, var, false, false) => {
-                    format!("written += write_var_array(&self.{}, {}, out)?;", name, var)
-                        .parse()
-                        .unwrap()
-                }
-                _ => "".to_string().parse().unwrap(),
-            }
+            let size = member_size(i);
+            quote!(size += #size;)
         })
-        .collect())
+        .collect()
+}
+
+// The struct's or variant's size if it is constant, starting from the
+// `prefix` sizes written before the members.
+fn get_const_size(prefix: &[usize], members: &[Member]) -> proc_macro2::TokenStream {
+    let sizes = members.iter().filter(|i| !i.skip).map(member_const_size);
+    quote!(__xdr::ser::sum_xdr_sizes(&[#(::std::option::Option::Some(#prefix),)* #(#sizes),*]))
 }
 
-fn get_calls_struct_in_xdr(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    Ok(members
+fn get_calls_fields_out_xdr(members: &[Member]) -> Vec<proc_macro2::TokenStream> {
+    members
         .iter()
-        .map(|i| match (&i.name, i.fixed, i.var, &i.v_type) {
-            (name, 0, 0, v_type) => format!(
-                "let {}_result = {}::read_xdr(&buffer[read as usize..])?; read += {}_result.1;",
-                name,
-                v_type.to_string().replace("<", "::<"),
-                name
-            )
-            .parse()
-            .unwrap(),
+        .filter(|i| !i.skip)
+        .map(|i| member_write(i, false))
+        .collect()
+}
 
-            (name, fixed, 0, v_type) if v_type.to_string().replace(" ", "") != "Vec<u8>" => {
-                format!(
-                "let {}_result: ({}, u64) = read_fixed_array({}, &buffer[read as usize..])?; read += {}_result.1;",
-                name, v_type, fixed, name
-            )
-                .parse()
-                .unwrap()
+fn get_calls_fields_in_xdr(
+    members: &[Member],
+    typedef: bool,
+    borrowed: bool,
+) -> Vec<proc_macro2::TokenStream> {
+    members
+        .iter()
+        .map(|i| {
+            let result = result_ident(i);
+            let ty = &i.v_type;
+            if i.skip {
+                return quote!(let #result: (#ty, u64) = (::std::default::Default::default(), 0););
             }
-            (name, 0, var, v_type) if v_type.to_string() == "String" => format!(
-                "let {}_result: ({}, u64) = read_var_string({}, &buffer[read as usize..])?; read += {}_result.1;",
-                name, v_type, var, name
-            )
-            .parse()
-            .unwrap(),
-            (name, 0, var, v_type) if v_type.to_string().replace(" ", "") != "Vec<u8>" => format!(
-                "let {}_result: ({}, u64) = read_var_array({}, &buffer[read as usize..])?; read += {}_result.1;",
-                name, v_type, var, name
-            )
-            .parse()
-            .unwrap(),
-            (name, fixed, 0, v_type) => format!(
-                "let {}_result: ({}, u64) = read_fixed_opaque({}, &buffer[read as usize..])?; read += {}_result.1;",
-                name, v_type, fixed, name
-            )
-            .parse()
-            .unwrap(),
-            (name, 0, var, v_type) => format!(
-                "let {}_result: ({}, u64) = read_var_opaque({}, &buffer[read as usize..])?; read += {}_result.1;",
-                name, v_type, var, name
-            )
-            .parse()
-            .unwrap(),
-            _ => "".to_string().parse().unwrap(),
+            let source = if borrowed {
+                Source::BorrowedXdr
+            } else {
+                Source::Xdr
+            };
+            let call = member_read(i, source);
+            let key = &i.key;
+            let call = if typedef {
+                quote!(#call?)
+            } else {
+                quote!(reader.field(#key, |reader| #call)?)
+            };
+            quote!(let #result: (#ty, u64) = #call; read += #result.1;)
         })
-        .collect())
+        .collect()
 }
 
-fn get_calls_struct_in_json(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    let obj_fun = |i: &proc_macro2::Ident| -> String {
-        if members.len() == 1 && members[0].name == "t" {
-            return "jval".to_string();
-        }
-        format!(
-            r#"obj.ok_or_else(|| Error::invalid_json())?.get("{}").ok_or_else(|| Error::invalid_json())?"#,
-            i
-        )
-    };
-    Ok(members
+fn get_calls_fields_in_json(
+    members: &[Member],
+    tuple: bool,
+    typedef: bool,
+) -> Vec<proc_macro2::TokenStream> {
+    members
         .iter()
-        .map(|i| match (&i.name, i.fixed, i.var, &i.v_type) {
-            (name, 0, 0, v_type) => format!(
-                r#"let {}_result = {}::read_json({}.clone())?;"#,
-                name,
-                v_type.to_string().replace("<", "::<"),
-                obj_fun(name)
-            )
-            .parse()
-            .unwrap(),
-            (name, fixed, 0, v_type) if v_type.to_string().replace(" ", "") != "Vec<u8>" => {
-                format!(
-                    r#"let {}_result: {} = read_fixed_array_json({}, {}.clone())?;"#,
-                    name,
-                    v_type,
-                    fixed,
-                    obj_fun(name)
-                )
-                .parse()
-                .unwrap()
-            }
-            (name, 0, var, v_type) if v_type.to_string() == "String" => format!(
-                r#"let {}_result: {} = read_var_string_json({}, {}.clone())?;"#,
-                name,
-                v_type,
-                var,
-                obj_fun(name)
-            )
-            .parse()
-            .unwrap(),
-            (name, 0, var, v_type) if v_type.to_string().replace(" ", "") != "Vec<u8>" => format!(
-                r#"let {}_result: {} = read_var_array_json({}, {}.clone())?;"#,
-                name,
-                v_type,
-                var,
-                obj_fun(name)
-            )
-            .parse()
-            .unwrap(),
-            (name, fixed, 0, v_type) => format!(
-                r#"let {}_result: {} = read_fixed_opaque_json({}, {}.clone())?;"#,
-                name,
-                v_type,
-                fixed,
-                obj_fun(name)
-            )
-            .parse()
-            .unwrap(),
-            (name, 0, var, v_type) => format!(
-                r#"let {}_result: {} = read_var_opaque_json({}, {}.clone())?;"#,
-                name,
-                v_type,
-                var,
-                obj_fun(name)
-            )
-            .parse()
-            .unwrap(),
-            _ => "".to_string().parse().unwrap(),
+        .map(|i| {
+            let result = result_ident(i);
+            let ty = &i.v_type;
+            if i.skip {
+                return quote!(let #result: #ty = ::std::default::Default::default(););
+            }
+            let key = &i.key;
+            let lookup = if tuple {
+                let index: usize = key.parse().unwrap();
+                quote!(arr.ok_or_else(|| __xdr::error::Error::invalid_json())?.get(#index))
+            } else {
+                quote!(obj.ok_or_else(|| __xdr::error::Error::invalid_json())?.get(#key))
+            };
+            let value = if typedef {
+                quote!(jval.clone())
+            } else if i.default {
+                quote!(val.clone())
+            } else {
+                quote!(#lookup.ok_or_else(|| __xdr::error::Error::invalid_json().in_field(#key))?.clone())
+            };
+            let call = member_read(i, Source::Json(value));
+            let call = if typedef {
+                quote!(#call?)
+            } else if i.default {
+                quote! {
+                    match #lookup {
+                        Some(val) => #call.map_err(|e| e.in_field(#key))?,
+                        None => ::std::default::Default::default(),
+                    }
+                }
+            } else {
+                quote!(#call.map_err(|e| e.in_field(#key))?)
+            };
+            quote!(let #result: #ty = #call;)
         })
-        .collect())
+        .collect()
 }
 
-fn get_struct_build_in_xdr(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    Ok(members
+fn get_build_in_xdr(members: &[Member]) -> Vec<proc_macro2::TokenStream> {
+    members
         .iter()
-        .map(|i| match (&i.name, i.fixed, i.var) {
-            (name, _, _) => format!("{}: {}_result.0,", name, name).parse().unwrap(),
+        .map(|i| {
+            let access: proc_macro2::TokenStream = i.access.parse().unwrap();
+            let result = result_ident(i);
+            quote!(#access: #result.0,)
         })
-        .collect())
+        .collect()
 }
 
-fn get_struct_build_in_json(data: &syn::DataStruct) -> Result<Vec<proc_macro2::TokenStream>, ()> {
-    let members = get_members(data)?;
-    Ok(members
+fn get_build_in_json(members: &[Member]) -> Vec<proc_macro2::TokenStream> {
+    members
         .iter()
-        .map(|i| match (&i.name, i.fixed, i.var) {
-            (name, _, _) => format!("{}: {}_result,", name, name).parse().unwrap(),
+        .map(|i| {
+            let access: proc_macro2::TokenStream = i.access.parse().unwrap();
+            let result = result_ident(i);
+            quote!(#access: #result,)
         })
-        .collect())
+        .collect()
+}
+
+// Binds `obj` or `arr` to the JSON container holding the members.
+fn json_container(tuple: bool) -> proc_macro2::TokenStream {
+    if tuple {
+        quote! {
+            let arr = match &jval {
+                __xdr::json::JsonValue::Array(a) => Some(a),
+                _ => None
+            };
+        }
+    } else {
+        quote! {
+            let obj = match &jval {
+                __xdr::json::JsonValue::Object(o) => Some(o),
+                _ => None
+            };
+        }
+    }
+}
+
+fn get_container_attrs(ast: &syn::DeriveInput) -> syn::Result<ContainerAttrs> {
+    let mut attrs = ContainerAttrs {
+        krate: syn::parse_quote!(::xdr_rs_serialize),
+        transparent: None,
+    };
+    for meta_item in get_xdr_meta_items(&ast.attrs)? {
+        match meta_item {
+            Meta(NameValue(ref m)) if m.path.is_ident("crate") => match m.lit {
+                syn::Lit::Str(ref val) => {
+                    attrs.krate = val.parse().map_err(|_| {
+                        syn::Error::new_spanned(
+                            val,
+                            "expected a path, e.g. `crate = \"::xdr_rs_serialize\"`",
+                        )
+                    })?
+                }
+                ref lit => return Err(syn::Error::new_spanned(lit, "expected a string path")),
+            },
+            Meta(syn::Meta::Path(ref p)) if p.is_ident("transparent") => {
+                if let syn::Data::Enum(_) = ast.data {
+                    return Err(syn::Error::new_spanned(
+                        p,
+                        "only structs can be transparent",
+                    ));
+                }
+                attrs.transparent = Some(p.clone());
+            }
+            meta_item => {
+                return Err(syn::Error::new_spanned(
+                    meta_item,
+                    "expected `crate = \"...\"` or `transparent`",
+                ))
+            }
+        }
+    }
+    Ok(attrs)
 }
 
-fn impl_xdr_out_macro(ast: &syn::DeriveInput) -> TokenStream {
+// The impls refer to the library only through the `__xdr` alias, so deriving
+// needs no imports. `#[xdr(crate = "...")]` changes the path the alias points
+// at, for crates that use the library through a re-export.
+fn wrap_in_const(
+    attrs: &ContainerAttrs,
+    trait_path: proc_macro2::TokenStream,
+    gen: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let krate = &attrs.krate;
+    quote! {
+        const _: () = {
+            use #krate as __xdr;
+            use #trait_path as _;
+            #gen
+        };
+    }
+}
+
+// Type parameters are bounded by the derived trait, as their values are
+// encoded and decoded through it.
+fn add_trait_bounds(mut generics: syn::Generics, bound: syn::TypeParamBound) -> syn::Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
+    generics
+}
+
+fn impl_xdr_out_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &ast.ident;
+    let attrs = get_container_attrs(ast)?;
+    let generics = add_trait_bounds(ast.generics.clone(), syn::parse_quote!(__xdr::ser::XDROut));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let gen = match &ast.data {
         syn::Data::Struct(data) => {
-            let xdr_calls = get_calls_struct_out_xdr(data).unwrap();
-            let json_calls = get_calls_struct_out_json(data).unwrap();
+            let members = get_struct_members(data)?;
+            let xdr_calls = get_calls_fields_out_xdr(&members);
+            let json_calls = get_calls_fields_out_json(
+                &members,
+                is_tuple(&data.fields),
+                is_transparent(&attrs, data, &members)?,
+            );
+            let size_calls = get_calls_fields_size(&members);
+            let const_size = get_const_size(&[], &members);
             quote! {
-                impl XDROut for #name {
-                    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+                impl #impl_generics __xdr::ser::XDROut for #name #ty_generics #where_clause {
+                    const XDR_SIZE: ::std::option::Option<usize> = #const_size;
+
+                    fn write_xdr<W: ::std::io::Write + ?Sized>(&self, out: &mut W) -> ::std::result::Result<u64, __xdr::error::Error> {
                         let mut written: u64 = 0;
                         #(#xdr_calls)*
                         Ok(written)
                     }
 
-                    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+                    fn write_json<W: ::std::io::Write + ?Sized>(&self, out: &mut W) -> ::std::result::Result<u64, __xdr::error::Error> {
                         let mut written: u64 = 0;
                         #(#json_calls)*
                         Ok(written)
                     }
+
+                    fn xdr_size(&self) -> u64 {
+                        let mut size: u64 = 0;
+                        #(#size_calls)*
+                        size
+                    }
                 }
             }
         }
         syn::Data::Enum(data) => {
-            let xdr_matches = get_calls_enum_out_xdr(data).unwrap();
-            let json_matches = get_calls_enum_out_json(data).unwrap();
+            let xdr_matches = get_calls_enum_out_xdr(data)?;
+            let json_matches = get_calls_enum_out_json(data)?;
             let names = std::iter::repeat(name);
             let names2 = std::iter::repeat(name);
+            let (size_arms, const_size) = get_calls_enum_size(data, name)?;
+            let discriminant_check = get_default_discriminant_check(data)?;
             quote! {
-                impl XDROut for #name {
-                    fn write_xdr(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+                #discriminant_check
+                impl #impl_generics __xdr::ser::XDROut for #name #ty_generics #where_clause {
+                    const XDR_SIZE: ::std::option::Option<usize> = #const_size;
+
+                    fn write_xdr<W: ::std::io::Write + ?Sized>(&self, out: &mut W) -> ::std::result::Result<u64, __xdr::error::Error> {
                         match *self {
                             #(#names::#xdr_matches)*
-                            _ => Err(Error::invalid_enum_value())
+                            _ => Err(__xdr::error::Error::invalid_enum_value())
                         }
                     }
 
-                    fn write_json(&self, out: &mut Vec<u8>) -> Result<u64, Error> {
+                    fn write_json<W: ::std::io::Write + ?Sized>(&self, out: &mut W) -> ::std::result::Result<u64, __xdr::error::Error> {
                         match *self {
                             #(#names2::#json_matches)*
-                            _ => Err(Error::invalid_enum_value())
+                            _ => Err(__xdr::error::Error::invalid_enum_value())
+                        }
+                    }
+
+                    fn xdr_size(&self) -> u64 {
+                        match *self {
+                            #(#size_arms)*
                         }
                     }
                 }
             }
         }
-        _ => panic!("XDROut macro only works with enums and structs."),
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "XDROut can only be derived for structs and enums",
+            ))
+        }
     };
-    gen.into()
+    Ok(wrap_in_const(&attrs, quote!(__xdr::ser::XDROut), gen))
 }
 
-fn impl_xdr_in_macro(ast: &syn::DeriveInput) -> TokenStream {
+fn impl_xdr_in_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &ast.ident;
+    let attrs = get_container_attrs(ast)?;
+    let generics = add_trait_bounds(ast.generics.clone(), syn::parse_quote!(__xdr::de::XDRIn));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let gen = match &ast.data {
         syn::Data::Struct(data) => {
-            let xdr_calls = get_calls_struct_in_xdr(data).unwrap();
-            let json_calls = get_calls_struct_in_json(data).unwrap();
-            let struct_build_xdr = get_struct_build_in_xdr(data).unwrap();
-            let struct_build_json = get_struct_build_in_json(data).unwrap();
+            let members = get_struct_members(data)?;
+            let typedef = is_transparent(&attrs, data, &members)?;
+            let tuple = is_tuple(&data.fields);
+            let xdr_calls = get_calls_fields_in_xdr(&members, typedef, false);
+            let json_calls = get_calls_fields_in_json(&members, tuple, typedef);
+            let struct_build_xdr = get_build_in_xdr(&members);
+            let struct_build_json = get_build_in_json(&members);
+            let json_container = json_container(tuple);
             quote! {
-                impl XDRIn for #name {
-                    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
+                impl #impl_generics __xdr::de::XDRIn for #name #ty_generics #where_clause {
+                    fn read_xdr_from<R: ::std::io::Read>(reader: &mut __xdr::de::XDRReader<R>) -> ::std::result::Result<(Self, u64), __xdr::error::Error> {
                         let mut read: u64 = 0;
                         #(#xdr_calls)*
                         Ok((
@@ -647,11 +1506,8 @@ fn impl_xdr_in_macro(ast: &syn::DeriveInput) -> TokenStream {
                         ))
                     }
 
-                    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
-                        let obj = match &jval {
-                            json::JsonValue::Object(o) => Some(o),
-                            _ => None
-                        };
+                    fn read_json(jval: __xdr::json::JsonValue) -> ::std::result::Result<Self, __xdr::error::Error> {
+                        #json_container
                         #(#json_calls)*
                         Ok( #name {
                             #(#struct_build_json)*
@@ -662,45 +1518,117 @@ fn impl_xdr_in_macro(ast: &syn::DeriveInput) -> TokenStream {
             }
         }
         syn::Data::Enum(data) => {
-            let matches_xdr = get_calls_enum_in_xdr(data, name).unwrap();
-            let matches_json1 = get_calls_enum_in_json(data, name).unwrap();
-            let matches_json2 = get_calls_enum_in_json(data, name).unwrap();
+            let (_, discriminant) = get_enums(data)?;
+            let discriminant_type: proc_macro2::TokenStream =
+                discriminant.rust_type().parse().unwrap();
+            let matches_xdr = get_calls_enum_in_xdr(data, name)?;
+            let matches_json1 = get_calls_enum_in_json(data, name)?;
+            let matches_json2 = get_calls_enum_in_json(data, name)?;
+            let discriminant_check = get_default_discriminant_check(data)?;
+            // Plain enums may also be written as just their value.
+            let json_number = match discriminant {
+                Discriminant::Int => quote! {
+                    __xdr::json::JsonValue::Number(num) =>  {
+                        let enum_index : i32 = f64::from(num) as i32;
+                        let enum_val : __xdr::json::JsonValue = __xdr::json::JsonValue::new_object();
+                        match enum_index {
+                            #(#matches_json2)*
+                        }
+                    },
+                },
+                _ => quote!(),
+            };
             quote! {
-                impl XDRIn for #name {
-                    fn read_xdr(buffer: &[u8]) -> Result<(Self, u64), Error> {
-                        let enum_val = i32::read_xdr(buffer)?.0;
+                #discriminant_check
+                impl #impl_generics __xdr::de::XDRIn for #name #ty_generics #where_clause {
+                    fn read_xdr_from<R: ::std::io::Read>(reader: &mut __xdr::de::XDRReader<R>) -> ::std::result::Result<(Self, u64), __xdr::error::Error> {
+                        let enum_val = <#discriminant_type>::read_xdr_from(reader)?.0;
                         match enum_val {
                             #(#matches_xdr)*
-                            _ => Err(Error::invalid_enum_value())
                         }
                     }
 
-                    fn read_json(jval: json::JsonValue) -> Result<Self, Error> {
+                    fn read_json(jval: __xdr::json::JsonValue) -> ::std::result::Result<Self, __xdr::error::Error> {
                         match jval {
-                            json::JsonValue::Object(obj) =>  {
-                                let enum_index = i32::read_json(obj.get("type").ok_or_else(|| Error::invalid_json())?.clone())?;
-                                let enum_val = obj.get("data").ok_or_else(|| Error::invalid_json())?;
+                            __xdr::json::JsonValue::Object(obj) =>  {
+                                let enum_index = <#discriminant_type>::read_json(obj.get("type").ok_or_else(|| __xdr::error::Error::invalid_json())?.clone())?;
+                                let enum_val = obj.get("data").ok_or_else(|| __xdr::error::Error::invalid_json())?;
                                 match enum_index {
                                     #(#matches_json1)*
-                                    _ => Err(Error::invalid_enum_value())
-                                }
-                            },
-                            json::JsonValue::Number(num) =>  {
-                                let enum_index : i32 = f64::from(num) as i32;
-                                let enum_val : json::JsonValue = json::JsonValue::new_object();
-                                match enum_index {
-                                    #(#matches_json2)*
-                                    _ => Err(Error::invalid_enum_value())
                                 }
                             },
-                            _ => Err(Error::invalid_enum_value())
+                            #json_number
+                            _ => Err(__xdr::error::Error::invalid_enum_value())
                         }
                     }
                 }
             }
         }
-        _ => panic!("XDRIn macro only works with enums and structs."),
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "XDRIn can only be derived for structs and enums",
+            ))
+        }
     };
+    Ok(wrap_in_const(&attrs, quote!(__xdr::de::XDRIn), gen))
+}
 
-    gen.into()
+// Decoding borrows from a buffer that outlives every lifetime of the type.
+fn add_borrowed_lifetime(mut generics: syn::Generics) -> syn::Generics {
+    let mut buffer = syn::LifetimeDef::new(borrowed_lifetime());
+    for param in generics.lifetimes() {
+        buffer.bounds.push(param.lifetime.clone());
+    }
+    generics
+        .params
+        .insert(0, syn::GenericParam::Lifetime(buffer));
+    generics
+}
+
+fn impl_xdr_in_borrowed_macro(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+    let attrs = get_container_attrs(ast)?;
+    let lifetime = borrowed_lifetime();
+    let generics = add_trait_bounds(
+        add_borrowed_lifetime(ast.generics.clone()),
+        syn::parse_quote!(__xdr::de::XDRInBorrowed<#lifetime>),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let gen = match &ast.data {
+        syn::Data::Struct(data) => {
+            let members = get_struct_members(data)?;
+            let typedef = is_transparent(&attrs, data, &members)?;
+            let xdr_calls = get_calls_fields_in_xdr(&members, typedef, true);
+            let struct_build_xdr = get_build_in_xdr(&members);
+            quote! {
+                impl #impl_generics __xdr::de::XDRInBorrowed<#lifetime> for #name #ty_generics #where_clause {
+                    fn read_xdr_borrowed_from(reader: &mut __xdr::de::XDRReader<&#lifetime [u8]>) -> ::std::result::Result<(Self, u64), __xdr::error::Error> {
+                        let mut read: u64 = 0;
+                        #(#xdr_calls)*
+                        Ok((
+                            #name {
+                              #(#struct_build_xdr)*
+                            },
+                            read
+                        ))
+                    }
+                }
+            }
+        }
+        syn::Data::Enum(data) => {
+            return Err(syn::Error::new_spanned(
+                data.enum_token,
+                "XDRInBorrowed can only be derived for structs",
+            ))
+        }
+        syn::Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "XDRInBorrowed can only be derived for structs",
+            ))
+        }
+    };
+    Ok(wrap_in_const(&attrs, quote!(__xdr::de::XDRInBorrowed), gen))
 }